@@ -0,0 +1,381 @@
+use std::path::PathBuf;
+
+use bevy::{
+    asset::AssetPlugin,
+    audio::AudioPlugin,
+    hierarchy::HierarchyPlugin,
+    input::InputPlugin,
+    transform::TransformPlugin,
+    window::PresentMode,
+};
+
+use crate::{
+    prelude::*,
+    SpaceShooterPlugins,
+};
+
+/// Assembles the game `App`, either in a window or headless.
+///
+/// A headless app has no window, audio nor rendering. It runs exactly one
+/// simulation step per `App::update` and its ship is driven from code, see
+/// `ScriptedShipInput`.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Default)]
+pub struct GameAppBuilder {
+    headless: bool,
+    start_in_game: bool,
+    seed: Option<u64>,
+    config: GameConfig,
+    config_watch: Option<PathBuf>,
+    difficulty: Difficulty,
+    arena_mode: ArenaMode,
+    director: bool,
+    skin: Option<String>,
+    window_size: Option<Vec2>,
+    present_mode: Option<PresentMode>,
+    mute: bool,
+    debug_render: bool,
+    record: Option<PathBuf>,
+    playback: Option<Replay>,
+}
+
+impl GameAppBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub const fn headless(mut self, headless: bool) -> Self {
+        self.headless = headless;
+        self
+    }
+
+    /// Skip the start menu.
+    #[must_use]
+    pub const fn start_in_game(mut self, start_in_game: bool) -> Self {
+        self.start_in_game = start_in_game;
+        self
+    }
+
+    #[must_use]
+    pub const fn seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    #[must_use]
+    pub const fn config(mut self, config: GameConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Reload the config whenever this file changes, unless recording or
+    /// playing back.
+    #[must_use]
+    pub fn watch_config(mut self, path: Option<PathBuf>) -> Self {
+        self.config_watch = path;
+        self
+    }
+
+    #[must_use]
+    pub const fn difficulty(mut self, difficulty: Difficulty) -> Self {
+        self.difficulty = difficulty;
+        self
+    }
+
+    /// Arena mode selected when the start menu opens, or of the game when
+    /// starting in game.
+    #[must_use]
+    pub const fn arena_mode(mut self, arena_mode: ArenaMode) -> Self {
+        self.arena_mode = arena_mode;
+        self
+    }
+
+    /// Let the `Director` spawn asteroids and guardians, instead of the
+    /// `Waves`.
+    #[must_use]
+    pub const fn director(mut self, director: bool) -> Self {
+        self.director = director;
+        self
+    }
+
+    /// Skin pack folder in `assets/skins`.
+    #[must_use]
+    pub fn skin(mut self, skin: Option<String>) -> Self {
+        self.skin = skin;
+        self
+    }
+
+    /// Size of the window and of the arena, instead of the configured one.
+    #[must_use]
+    pub const fn window_size(mut self, size: Option<Vec2>) -> Self {
+        self.window_size = size;
+        self
+    }
+
+    /// Defaults to `PresentMode::AutoVsync`.
+    #[must_use]
+    pub const fn present_mode(mut self, present_mode: Option<PresentMode>) -> Self {
+        self.present_mode = present_mode;
+        self
+    }
+
+    /// Leave Bevy's audio out, the game plays silently.
+    #[must_use]
+    pub const fn mute(mut self, mute: bool) -> Self {
+        self.mute = mute;
+        self
+    }
+
+    /// Draw Rapier's colliders on top of the sprites.
+    #[must_use]
+    pub const fn debug_render(mut self, debug_render: bool) -> Self {
+        self.debug_render = debug_render;
+        self
+    }
+
+    #[must_use]
+    pub fn record(mut self, path: Option<PathBuf>) -> Self {
+        self.record = path;
+        self
+    }
+
+    /// Play `replay` back, with its own seed and `ReplaySettings`.
+    #[must_use]
+    pub fn playback(mut self, replay: Option<Replay>) -> Self {
+        if let Some(replay) = &replay {
+            self.seed = Some(replay.seed);
+            self.difficulty = replay.settings.difficulty;
+            self.arena_mode = replay.settings.arena_mode;
+            self.window_size = Some(replay.settings.arena_size);
+            self.director = replay.settings.director;
+            self.config = replay.settings.config.clone();
+        }
+        self.playback = replay;
+        self
+    }
+
+    pub fn build(mut self) -> App {
+        let mut app = App::new();
+        // Replays need the settings the game started with until it ends.
+        let replay: bool = self.record.is_some() || self.playback.is_some();
+
+        if let Some(size) = self.window_size {
+            self.config.arena.width = size.x;
+            self.config.arena.height = size.y;
+        }
+
+        if self.headless {
+            app.add_plugins(MinimalPlugins)
+                .add_plugin(AssetPlugin::default())
+                // Rapier's async colliders need the mesh storage.
+                .add_asset::<Mesh>()
+                .add_plugin(TransformPlugin)
+                .add_plugin(HierarchyPlugin)
+                .add_plugin(InputPlugin)
+                .insert_resource(ScriptedShipInput);
+        } else {
+            app.insert_resource(ClearColor(Color::rgb_u8(0, 0, 0))); // 0.01, 0.1, 0.001
+            let default_plugins = DefaultPlugins.set(WindowPlugin {
+                window: WindowDescriptor {
+                    title: "SpaceGuardian".to_string(),
+                    width: self.config.arena.width,
+                    height: self.config.arena.height,
+                    present_mode: self.present_mode.unwrap_or(PresentMode::AutoVsync),
+                    ..default()
+                },
+                ..default()
+            });
+            if self.mute {
+                app.add_plugins(default_plugins.disable::<AudioPlugin>());
+            } else {
+                app.add_plugins(default_plugins);
+            }
+
+            // Compute shaders are not supported on WASM.
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                app.add_plugin(crate::particle_effects::ParticleEffectsPlugin);
+            }
+
+            if self.debug_render {
+                app.add_plugin(RapierDebugRenderPlugin::default());
+            }
+
+            app.add_startup_system(setup_camera);
+        }
+
+        let plugins = SpaceShooterPlugins
+            .set(GameConfigPlugin {
+                config: self.config,
+                difficulty: self.difficulty,
+                watch: if replay { None } else { self.config_watch },
+            })
+            .set(AssetsPlugin { skin: self.skin })
+            .set(ArenaPlugin { mode: self.arena_mode, fixed_size: replay })
+            .set(DirectorPlugin { enabled: self.director })
+            .set(SimulationPlugin { step_per_update: self.headless })
+            .set(RngPlugin { seed: self.seed })
+            .set(ReplayPlugin { record: self.record, playback: self.playback })
+            .set(StatesPlugin { start_in_game: self.start_in_game });
+        if self.headless {
+            app.add_plugins(plugins.disable::<BackgroundPlugin>());
+        } else {
+            app.add_plugins(plugins);
+        }
+
+        app
+    }
+}
+
+/// Run `ticks` simulation steps of a headless app.
+pub fn run_ticks(app: &mut App, ticks: u64) {
+    for _ in 0..ticks {
+        app.update();
+    }
+}
+
+//----------------------------------------------------------------
+
+pub fn setup_camera(mut commands: Commands) {
+    commands.spawn(Camera2dBundle::default());
+}
+
+//----------------------------------------------------------------
+
+/// Helpers driving a headless game, shared by the tests of every module.
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use super::*;
+
+    pub fn headless_game(seed: u64) -> App {
+        let mut app =
+            GameAppBuilder::new().headless(true).start_in_game(true).seed(Some(seed)).build();
+        run_ticks(&mut app, 1u64);
+        app
+    }
+
+    pub fn press(app: &mut App, actions: &[PlayerAction]) {
+        let mut ships = app.world.query_filtered::<&mut ActionState<PlayerAction>, With<Ship>>();
+        for mut action_state in ships.iter_mut(&mut app.world) {
+            action_state.release_all();
+            for action in actions {
+                action_state.press(*action);
+            }
+        }
+    }
+
+    pub fn ship(app: &mut App) -> Option<&mut Ship> {
+        let mut ships = app.world.query::<&mut Ship>();
+        ships.iter_mut(&mut app.world).next().map(Mut::into_inner)
+    }
+
+    pub fn bounds(app: &App) -> ArenaBounds {
+        *app.world.resource::<ArenaBounds>()
+    }
+
+    pub fn spawn_asteroid(
+        app: &mut App, size: AsteroidSize, material: AsteroidMaterial, position: Vec2,
+        velocity: Vec2,
+    ) {
+        app.world.send_event(AsteroidSpawnEvent {
+            size,
+            material,
+            x: position.x,
+            y: position.y,
+            vx: velocity.x,
+            vy: velocity.y,
+            angvel: 0f32,
+            mass: None,
+            shape: None,
+        });
+    }
+
+    pub fn spawn_guardian(app: &mut App, size: GuardianSize, position: Vec2, velocity: Vec2) {
+        app.world.send_event(GuardianSpawnEvent {
+            size,
+            x: position.x,
+            y: position.y,
+            vx: velocity.x,
+            vy: velocity.y,
+            angvel: 0f32,
+            mass: None,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        test_utils::*,
+        *,
+    };
+
+    #[test]
+    fn test_headless_game_starts() {
+        let mut app = headless_game(1u64);
+        assert_eq!(app.world.resource::<State<AppGameState>>().current(), &AppGameState::Game);
+        assert_eq!(app.world.resource::<SimulationTick>().0, 1u64);
+        assert_eq!(ship(&mut app).map(|ship| ship.life), Some(START_LIFE));
+    }
+
+    #[test]
+    fn test_laser_hit_scores() {
+        let mut app = headless_game(2u64);
+        let (size, material) = (AsteroidSize::Small, AsteroidMaterial::Rock);
+        spawn_asteroid(&mut app, size, material, Vec2::new(0f32, 150f32), Vec2::ZERO);
+        press(&mut app, &[PlayerAction::Fire]);
+        run_ticks(&mut app, 60u64);
+        assert!(app.world.resource::<Arena>().score > 0u32);
+    }
+
+    #[test]
+    fn test_asteroid_contact_costs_life() {
+        let mut app = headless_game(3u64);
+        spawn_asteroid(&mut app, AsteroidSize::Big, AsteroidMaterial::Rock, Vec2::ZERO, Vec2::ZERO);
+        run_ticks(&mut app, 10u64);
+        assert_eq!(ship(&mut app).map(|ship| ship.life), Some(START_LIFE - 1u32));
+    }
+
+    #[test]
+    fn test_contact_across_edge() {
+        let mut app = headless_game(7u64);
+        let half_height: f32 = bounds(&app).half_size().y;
+        let position = Vec2::new(0f32, half_height + 20f32);
+        spawn_asteroid(&mut app, AsteroidSize::Big, AsteroidMaterial::Rock, position, Vec2::ZERO);
+        let mut ships = app.world.query_filtered::<&mut Transform, With<Ship>>();
+        ships.single_mut(&mut app.world).translation.y = 40f32 - half_height;
+        run_ticks(&mut app, 10u64);
+        assert_eq!(ship(&mut app).map(|ship| ship.life), Some(START_LIFE - 1u32));
+    }
+
+    #[test]
+    fn test_last_life_game_over() {
+        let mut app = headless_game(4u64);
+        if let Some(ship) = ship(&mut app) {
+            ship.life = 1u32;
+        }
+        spawn_asteroid(&mut app, AsteroidSize::Big, AsteroidMaterial::Rock, Vec2::ZERO, Vec2::ZERO);
+        run_ticks(&mut app, 10u64);
+        assert!(ship(&mut app).is_none());
+        assert_eq!(app.world.resource::<State<AppGameState>>().current(), &AppGameState::GameOver);
+    }
+
+    #[test]
+    fn test_same_seed_same_game() {
+        let run = || {
+            let mut app = headless_game(5u64);
+            for i in 0..20 {
+                let turn =
+                    if i % 2 == 0 { PlayerAction::RotateLeft } else { PlayerAction::RotateRight };
+                press(&mut app, &[PlayerAction::Forward, PlayerAction::Fire, turn]);
+                run_ticks(&mut app, 30u64);
+            }
+            let mut asteroids = app.world.query_filtered::<&Transform, With<Asteroid>>();
+            let positions: Vec<Vec3> =
+                asteroids.iter(&app.world).map(|transform| transform.translation).collect();
+            (app.world.resource::<Arena>().score, positions)
+        };
+        assert_eq!(run(), run());
+    }
+}
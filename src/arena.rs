@@ -1,9 +1,61 @@
+use bevy::window::WindowResized;
+
 use crate::prelude::*;
 
-#[derive(Resource)]
-pub struct WinSize {
-    pub width: f32,
-    pub height: f32,
+/// Size of the arena in `px`, centered on the origin. Starts at the
+/// configured size and follows the window when it's resized.
+#[derive(Debug, Clone, Copy, PartialEq, Resource)]
+pub struct ArenaBounds {
+    pub size: Vec2,
+}
+
+impl ArenaBounds {
+    pub fn half_size(&self) -> Vec2 {
+        self.size / 2f32
+    }
+
+    /// `position` brought back into the arena through the opposite edges.
+    pub fn wrap(&self, position: Vec2) -> Vec2 {
+        let half_size: Vec2 = self.half_size();
+        Vec2::new(
+            (position.x + half_size.x).rem_euclid(self.size.x) - half_size.x,
+            (position.y + half_size.y).rem_euclid(self.size.y) - half_size.y,
+        )
+    }
+
+    /// Shortest offset from `from` to `to` in a wraparound arena, possibly
+    /// across the edges.
+    pub fn wrapped_offset(&self, from: Vec2, to: Vec2) -> Vec2 {
+        let offset: Vec2 = to - from;
+        offset - (offset / self.size).round() * self.size
+    }
+}
+
+/// What happens at the edges of the arena, chosen before a game starts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Resource, clap::ValueEnum)]
+pub enum ArenaMode {
+    /// Leaving through an edge enters through the opposite one.
+    #[default]
+    Wraparound,
+    /// `Wall`s line the edges and everything bounces off them.
+    Bounded,
+}
+
+impl ArenaMode {
+    #[must_use]
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Wraparound => Self::Bounded,
+            Self::Bounded => Self::Wraparound,
+        }
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Wraparound => "wraparound",
+            Self::Bounded => "bounded",
+        }
+    }
 }
 
 #[derive(Debug, Resource)]
@@ -13,41 +65,148 @@ pub struct Arena {
     pub score: u32,
 }
 
-pub struct ArenaPlugin;
+/// Adds the `Arena`, starting in the `ArenaMode` given here.
+#[derive(Debug, Default)]
+pub struct ArenaPlugin {
+    pub mode: ArenaMode,
+    /// Keep the `ArenaBounds` at their starting size when the window is
+    /// resized, as replays need.
+    pub fixed_size: bool,
+}
 
 impl Plugin for ArenaPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system_set(SystemSet::on_enter(AppState::Game).with_system(spawn_arena))
-            .add_system_set(SystemSet::on_update(AppState::Game).with_system(movement));
+        app.insert_resource(self.mode)
+            .add_startup_system_to_stage(StartupStage::PreStartup, setup_system)
+            // Also registered headless, without a window to resize.
+            .add_event::<WindowResized>()
+            .add_system_set(SystemSet::on_enter(AppState::Game).with_system(spawn_arena))
+            .add_system_set(SystemSet::on_update(AppState::Game).with_system(fit_walls))
+            .add_fixed_system_set(
+                SimulationStage::PostPhysics,
+                SystemSet::new()
+                    .with_run_criteria(run_in_game)
+                    .with_system(movement.label(StepOrder::Movement)),
+            );
+        if !self.fixed_size {
+            app.add_system(follow_window.before(fit_walls));
+        }
     }
 }
 
 //----------------------------------------------------------------
 
+fn setup_system(mut commands: Commands, config: Res<GameConfig>) {
+    commands.insert_resource(GuardianCount(0));
+
+    commands
+        .insert_resource(ArenaBounds { size: Vec2::new(config.arena.width, config.arena.height) });
+}
+
+/// Resize the arena with the primary window.
+fn follow_window(
+    windows: Option<Res<Windows>>, mut resized_events: EventReader<WindowResized>,
+    mut bounds: ResMut<ArenaBounds>,
+) {
+    let Some(primary) = windows.as_ref().and_then(|windows| windows.get_primary()) else { return };
+    let Some(event) = resized_events.iter().rfind(|event| event.id == primary.id()) else {
+        return;
+    };
+    let size = Vec2::new(event.width, event.height);
+    if size.min_element() > 0f32 && size != bounds.size {
+        bounds.size = size;
+    }
+}
+
 /// Spawns the arena.
 ///
 /// This function is called once when the game starts.
-fn spawn_arena(mut commands: Commands, mut rapier_cfg: ResMut<RapierConfiguration>) {
+fn spawn_arena(
+    mut commands: Commands, config: Res<GameConfig>, bounds: Res<ArenaBounds>,
+    mode: Res<ArenaMode>, mut rapier_cfg: ResMut<RapierConfiguration>,
+) {
     commands.insert_resource(Arena {
-        asteroid_spawn_timer: Timer::from_seconds(5f32, TimerMode::Once),
-        guardian_spawn_timer: Timer::from_seconds(8f32, TimerMode::Once),
+        asteroid_spawn_timer: Timer::from_seconds(
+            config.arena.asteroid_spawn_interval,
+            TimerMode::Once,
+        ),
+        guardian_spawn_timer: Timer::from_seconds(
+            config.arena.guardian_spawn_interval,
+            TimerMode::Once,
+        ),
         score: 0u32,
     });
 
     // Rapier configuration without gravity.
     rapier_cfg.gravity = Vec2::ZERO;
+
+    if *mode == ArenaMode::Bounded {
+        spawn_walls(&mut commands, &config, *bounds);
+    }
 }
 
-fn movement(mut query: Query<(&Velocity, &mut Transform)>) {
-    for (velocity, mut transform) in query.iter_mut() {
+/// Line the arena with four walls, their inner faces on its edges.
+fn spawn_walls(commands: &mut Commands, config: &GameConfig, bounds: ArenaBounds) {
+    let half_size: Vec2 = bounds.half_size();
+    let half_thickness: f32 = WALL_THICKNESS / 2f32;
+    let walls: [(Vec2, Vec2); 4] = [
+        (Vec2::new(0f32, half_size.y + half_thickness), Vec2::new(half_size.x, half_thickness)),
+        (
+            Vec2::new(0f32, (half_size.y + half_thickness).neg()),
+            Vec2::new(half_size.x, half_thickness),
+        ),
+        (Vec2::new(half_size.x + half_thickness, 0f32), Vec2::new(half_thickness, half_size.y)),
+        (
+            Vec2::new((half_size.x + half_thickness).neg(), 0f32),
+            Vec2::new(half_thickness, half_size.y),
+        ),
+    ];
+    for (position, half_extents) in walls {
+        commands.spawn((
+            TransformBundle::from(Transform::from_translation(position.extend(0f32))),
+            Wall,
+            ForState { states: vec![AppState::Game] },
+            RigidBody::Fixed,
+            Collider::cuboid(half_extents.x, half_extents.y),
+            Restitution {
+                coefficient: config.arena.wall_restitution,
+                combine_rule: CoefficientCombineRule::Max,
+            },
+        ));
+    }
+}
+
+/// Move the walls to the edges of a resized arena.
+fn fit_walls(
+    mut commands: Commands, config: Res<GameConfig>, bounds: Res<ArenaBounds>,
+    mode: Res<ArenaMode>, walls: Query<Entity, With<Wall>>,
+) {
+    if !bounds.is_changed() || *mode != ArenaMode::Bounded {
+        return; // early exit.
+    }
+    for entity in &walls {
+        commands.entity(entity).despawn_recursive();
+    }
+    spawn_walls(&mut commands, &config, *bounds);
+}
+
+/// Wrap around the screen edges, in `ArenaMode::Wraparound` only.
+fn movement(
+    bounds: Res<ArenaBounds>, mode: Res<ArenaMode>, mut query: Query<(&Velocity, &mut Transform)>,
+) {
+    if *mode != ArenaMode::Wraparound {
+        return; // early exit.
+    }
+    let half_size: Vec2 = bounds.half_size();
+
+    for (velocity, mut transform) in &mut query {
         let mut x: f32 = transform.translation.x;
         let mut y: f32 = transform.translation.y;
         // let mut updated: bool = Updated::False.into();
         let mut updated: bool = false;
 
         // Wrap around screen edges.
-        let half_width: f32 = ARENA_WIDTH / 2f32;
-        let half_height: f32 = ARENA_HEIGHT / 2f32;
+        let (half_width, half_height): (f32, f32) = (half_size.x, half_size.y);
 
         if x < half_width.neg() && velocity.linvel.x < 0f32 {
             x = half_width;
@@ -75,3 +234,30 @@ fn movement(mut query: Query<(&Velocity, &mut Transform)>) {
         }
     }
 }
+
+//----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_utils::*;
+
+    #[test]
+    fn test_bounded_arena_bounces() {
+        let mut app = GameAppBuilder::new()
+            .headless(true)
+            .start_in_game(true)
+            .arena_mode(ArenaMode::Bounded)
+            .seed(Some(6u64))
+            .build();
+        run_ticks(&mut app, 1u64);
+        let (size, material) = (AsteroidSize::Small, AsteroidMaterial::Rock);
+        spawn_asteroid(&mut app, size, material, Vec2::new(600f32, 0f32), Vec2::new(300f32, 0f32));
+        run_ticks(&mut app, 60u64);
+        let half_width: f32 = bounds(&app).half_size().x;
+        let mut asteroids = app.world.query_filtered::<(&Transform, &Velocity), With<Asteroid>>();
+        let (transform, velocity) = asteroids.single(&app.world);
+        assert!(transform.translation.x < half_width);
+        assert!(velocity.linvel.x < 0f32);
+    }
+}
@@ -1,3 +1,11 @@
+use std::collections::BTreeMap;
+
+use bevy::asset::{
+    HandleId,
+    LoadState,
+};
+use serde::Deserialize;
+
 use crate::prelude::*;
 
 #[derive(Debug, Resource)]
@@ -7,13 +15,11 @@ pub struct SpriteAssets {
     pub guardian_big: Handle<Image>,
     pub guardian_med: Handle<Image>,
     pub guardian_small: Handle<Image>,
-    pub meteor_big: Handle<Image>,
-    pub meteor_med: Handle<Image>,
-    pub meteor_small: Handle<Image>,
     pub ship_explosion: Handle<Image>,
     pub ship_contact: Handle<Image>,
     pub asteroid_explosion: Handle<Image>,
     pub guardian_explosion: Handle<Image>,
+    pub volatile_explosion: Handle<Image>,
 }
 #[derive(Debug, Resource)]
 pub struct AudioAssets {
@@ -22,6 +28,7 @@ pub struct AudioAssets {
     pub ship_contact: Handle<AudioSource>,
     pub asteroid_explosion: Handle<AudioSource>,
     pub guardian_explosion: Handle<AudioSource>,
+    pub volatile_explosion: Handle<AudioSource>,
 }
 
 #[derive(Debug, Resource)]
@@ -30,44 +37,472 @@ pub struct UiAssets {
     pub ship_life: UiImage,
 }
 
+impl SpriteAssets {
+    fn handles(&self) -> [HandleId; 10] {
+        [
+            self.player_ship.id(),
+            self.laser.id(),
+            self.guardian_big.id(),
+            self.guardian_med.id(),
+            self.guardian_small.id(),
+            self.ship_explosion.id(),
+            self.ship_contact.id(),
+            self.asteroid_explosion.id(),
+            self.guardian_explosion.id(),
+            self.volatile_explosion.id(),
+        ]
+    }
+}
+
+impl AudioAssets {
+    fn handles(&self) -> [HandleId; 6] {
+        [
+            self.laser_trigger.id(),
+            self.ship_explosion.id(),
+            self.ship_contact.id(),
+            self.asteroid_explosion.id(),
+            self.guardian_explosion.id(),
+            self.volatile_explosion.id(),
+        ]
+    }
+}
+
 //----------------------------------------------------------------
 
-pub struct AssetsPlugin;
+/// Names of the `AssetManifest` entries, one per field of the asset
+/// resources.
+const SPRITE_NAMES: [&str; 14] = [
+    "player_ship",
+    "laser",
+    "guardian_big",
+    "guardian_med",
+    "guardian_small",
+    "meteor_big",
+    "meteor_med",
+    "meteor_small",
+    "ship_explosion",
+    "ship_contact",
+    "asteroid_explosion",
+    "guardian_explosion",
+    "volatile_explosion",
+    "ship_life",
+];
+const SOUND_NAMES: [&str; 6] = [
+    "laser_trigger",
+    "ship_explosion",
+    "ship_contact",
+    "asteroid_explosion",
+    "guardian_explosion",
+    "volatile_explosion",
+];
+const FONT_NAMES: [&str; 1] = ["font"];
+/// Sprites sizing a collider or an asteroid outline with their `radius`.
+const RADIUS_NAMES: [&str; 7] = [
+    "player_ship",
+    "guardian_big",
+    "guardian_med",
+    "guardian_small",
+    "meteor_big",
+    "meteor_med",
+    "meteor_small",
+];
+
+/// A value written without `Some`, as in `radius: 13.5`.
+fn deserialize_some<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<f32>, D::Error> {
+    f32::deserialize(deserializer).map(Some)
+}
+
+/// An image of the `AssetManifest` and how it's drawn. The fields a skin
+/// pack leaves out keep their bundled value.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SpriteEntry {
+    /// Image file, empty for the entries only read for their `radius`.
+    pub path: String,
+    /// Collider radius in `px`, before `scale`, until the convex hull of
+    /// the image replaces it.
+    #[serde(deserialize_with = "deserialize_some")]
+    pub radius: Option<f32>,
+    /// Scale of both the sprite and its collider, `1.0` when omitted.
+    #[serde(deserialize_with = "deserialize_some")]
+    pub scale: Option<f32>,
+    /// Drawn size in `px`, the size of the image when omitted.
+    pub size: Option<(f32, f32)>,
+}
+
+impl SpriteEntry {
+    /// Collider radius, `0.0` for the sprites without one.
+    pub fn radius(&self) -> f32 {
+        self.radius.unwrap_or_default()
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale.unwrap_or(1f32)
+    }
+
+    pub fn transform_scale(&self) -> Vec3 {
+        Vec3::new(self.scale(), self.scale(), 1f32)
+    }
+
+    /// The fields set in `skin` replace these ones.
+    fn merge(&mut self, skin: Self) {
+        if !skin.path.is_empty() {
+            self.path = skin.path;
+        }
+        self.radius = skin.radius.or(self.radius);
+        self.scale = skin.scale.or(self.scale);
+        self.size = skin.size.or(self.size);
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for (field, value) in [("radius", self.radius), ("scale", self.scale)] {
+            match value {
+                Some(value) if value <= 0f32 || !value.is_finite() => {
+                    return Err(format!("{field} must be positive, got {value}"));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    pub fn custom_size(&self) -> Option<Vec2> {
+        self.size.map(|(width, height)| Vec2::new(width, height))
+    }
+}
+
+/// Asset files of the game by name, read from `MANIFEST_PATH` and optionally
+/// overridden by a skin pack.
+#[derive(Debug, Clone, PartialEq, Resource, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AssetManifest {
+    #[serde(default)]
+    pub sprites: BTreeMap<String, SpriteEntry>,
+    #[serde(default)]
+    pub sounds: BTreeMap<String, String>,
+    #[serde(default)]
+    pub fonts: BTreeMap<String, String>,
+}
+
+impl Default for AssetManifest {
+    /// The bundled manifest.
+    fn default() -> Self {
+        let manifest: Self = ron::from_str(include_str!("../assets/manifest.ron"))
+            .expect("bundled manifest should parse");
+        manifest
+    }
+}
+
+impl AssetManifest {
+    /// Read and validate a manifest, which must name every asset.
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be read, or `parse` fails or misses an entry.
+    pub fn load(path: &std::path::Path) -> Result<Self, String> {
+        let manifest: Self = Self::parse_file(path)?;
+        manifest.validate_complete().map_err(|err| format!("`{}`: {err}", path.display()))?;
+        Ok(manifest)
+    }
+
+    /// Parse a manifest, possibly partial.
+    ///
+    /// # Errors
+    ///
+    /// If `source` isn't a valid manifest, or names an unknown asset.
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let manifest: Self = ron::from_str(source).map_err(|err| err.to_string())?;
+        manifest.validate_names()?;
+        for (name, sprite) in &manifest.sprites {
+            sprite.validate().map_err(|err| format!("sprite `{name}`: {err}"))?;
+        }
+        Ok(manifest)
+    }
+
+    fn parse_file(path: &std::path::Path) -> Result<Self, String> {
+        let source: String = std::fs::read_to_string(path)
+            .map_err(|err| format!("could not read `{}`: {err}", path.display()))?;
+        Self::parse(&source).map_err(|err| format!("`{}`: {err}", path.display()))
+    }
+
+    /// Override entries with the ones of the skin pack in `skins/<name>/`.
+    ///
+    /// # Errors
+    ///
+    /// If the manifest of the skin pack can't be read or parsed.
+    pub fn load_skin(&mut self, name: &str) -> Result<(), String> {
+        let folder = std::path::Path::new(SKINS_DIR).join(name);
+        let skin: Self =
+            Self::parse_file(&asset_path(&folder.join(MANIFEST_PATH).to_string_lossy()))?;
+        self.apply_skin(&folder, skin);
+        Ok(())
+    }
+
+    fn apply_skin(&mut self, folder: &std::path::Path, skin: Self) {
+        let in_folder = |path: &str| folder.join(path).to_string_lossy().into_owned();
+        for (name, mut entry) in skin.sprites {
+            if !entry.path.is_empty() {
+                entry.path = in_folder(&entry.path);
+            }
+            self.sprites.entry(name).or_default().merge(entry);
+        }
+        for (name, path) in skin.sounds {
+            self.sounds.insert(name, in_folder(&path));
+        }
+        for (name, path) in skin.fonts {
+            self.fonts.insert(name, in_folder(&path));
+        }
+    }
+
+    fn validate_names(&self) -> Result<(), String> {
+        let unknown = (self.sprites.keys().filter(|name| !SPRITE_NAMES.contains(&name.as_str())))
+            .chain(self.sounds.keys().filter(|name| !SOUND_NAMES.contains(&name.as_str())))
+            .chain(self.fonts.keys().filter(|name| !FONT_NAMES.contains(&name.as_str())));
+        match unknown.cloned().collect::<Vec<String>>() {
+            names if names.is_empty() => Ok(()),
+            names => Err(format!("unknown assets: {}", names.join(", "))),
+        }
+    }
+
+    fn validate_complete(&self) -> Result<(), String> {
+        let missing = (SPRITE_NAMES.iter().filter(|name| !self.sprites.contains_key(**name)))
+            .chain(SOUND_NAMES.iter().filter(|name| !self.sounds.contains_key(**name)))
+            .chain(FONT_NAMES.iter().filter(|name| !self.fonts.contains_key(**name)));
+        match missing.copied().collect::<Vec<&str>>() {
+            names if names.is_empty() => {}
+            names => return Err(format!("missing assets: {}", names.join(", "))),
+        }
+        let no_radius = RADIUS_NAMES.iter().filter(|name| self.sprites[**name].radius.is_none());
+        match no_radius.copied().collect::<Vec<&str>>() {
+            names if names.is_empty() => Ok(()),
+            names => Err(format!("missing sprite radius: {}", names.join(", "))),
+        }
+    }
+
+    /// # Panics
+    ///
+    /// If `name` isn't one of the sprites, which a loaded manifest has all.
+    pub fn sprite(&self, name: &str) -> &SpriteEntry {
+        &self.sprites[name]
+    }
+}
+
+/// Progress text of the loading screen.
+#[derive(Component)]
+struct LoadingText;
+
+//----------------------------------------------------------------
+
+/// Load the `AssetManifest`, with the `skin` pack if any, and every asset it
+/// lists.
+#[derive(Debug, Default)]
+pub struct AssetsPlugin {
+    pub skin: Option<String>,
+}
 
 impl Plugin for AssetsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(setup);
+        let mut manifest: AssetManifest = match AssetManifest::load(&asset_path(MANIFEST_PATH)) {
+            Ok(manifest) => manifest,
+            Err(err) => {
+                error!("Asset manifest not loaded, using the bundled one: {err}");
+                AssetManifest::default()
+            }
+        };
+        if let Some(skin) = &self.skin {
+            match manifest.load_skin(skin) {
+                Ok(()) => info!("Using skin `{skin}`"),
+                Err(err) => error!("Skin not loaded: {err}"),
+            }
+        }
+
+        app.insert_resource(manifest)
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_enter(AppState::Loading).with_system(loading_spawn))
+            .add_system_set(SystemSet::on_update(AppState::Loading).with_system(loading_system));
     }
 }
 
 //----------------------------------------------------------------
 
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, manifest: Res<AssetManifest>) {
+    let sprite = |name: &str| asset_server.load(manifest.sprite(name).path.as_str());
+    let sound = |name: &str| asset_server.load(manifest.sounds[name].as_str());
+
     commands.insert_resource(SpriteAssets {
-        player_ship: asset_server.load("playerShip2_red.png"),
-        laser: asset_server.load("laserRed07.png"),
-        guardian_big: asset_server.load("sprite_sphere_256x256.png"),
-        guardian_med: asset_server.load("sprite_sphere_158x158.png"),
-        guardian_small: asset_server.load("sprite_sphere_97x97.png"),
-        meteor_big: asset_server.load("meteorBrown_big1.png"),
-        meteor_med: asset_server.load("meteorBrown_med1.png"),
-        meteor_small: asset_server.load("meteorBrown_small1.png"),
-        ship_explosion: asset_server.load("explosion01.png"),
-        ship_contact: asset_server.load("explosion01.png"),
-        asteroid_explosion: asset_server.load("flash00.png"),
-        guardian_explosion: asset_server.load("laserRed-7.png"),
+        player_ship: sprite("player_ship"),
+        laser: sprite("laser"),
+        guardian_big: sprite("guardian_big"),
+        guardian_med: sprite("guardian_med"),
+        guardian_small: sprite("guardian_small"),
+        ship_explosion: sprite("ship_explosion"),
+        ship_contact: sprite("ship_contact"),
+        asteroid_explosion: sprite("asteroid_explosion"),
+        guardian_explosion: sprite("guardian_explosion"),
+        volatile_explosion: sprite("volatile_explosion"),
     });
 
     commands.insert_resource(AudioAssets {
-        laser_trigger: asset_server.load("sfx_laser1.ogg"),
-        ship_explosion: asset_server.load("Explosion_ship.ogg"),
-        ship_contact: asset_server.load("Explosion.ogg"),
-        asteroid_explosion: asset_server.load("Explosion.ogg"),
-        guardian_explosion: asset_server.load("Explosion_ship.ogg"),
+        laser_trigger: sound("laser_trigger"),
+        ship_explosion: sound("ship_explosion"),
+        ship_contact: sound("ship_contact"),
+        asteroid_explosion: sound("asteroid_explosion"),
+        guardian_explosion: sound("guardian_explosion"),
+        volatile_explosion: sound("volatile_explosion"),
     });
 
     commands.insert_resource(UiAssets {
-        font: asset_server.load("kenvector_future.ttf"),
-        ship_life: asset_server.load("playerLife1_red.png").into(),
+        font: asset_server.load(manifest.fonts["font"].as_str()),
+        ship_life: sprite("ship_life").into(),
     });
 }
+
+fn loading_spawn(mut commands: Commands, assets: Res<UiAssets>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100f32), Val::Percent(100f32)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            ForState { states: vec![AppState::Loading] },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle {
+                    style: default(),
+                    text: Text::from_section(
+                        "loading",
+                        TextStyle {
+                            font: assets.font.clone(),
+                            font_size: 50f32,
+                            color: Color::rgb_u8(0x00, 0x44, 0x44),
+                        },
+                    ),
+                    ..default()
+                },
+                LoadingText,
+            ));
+        });
+}
+
+/// Leave `AppState::Loading` once every asset is loaded, or list the ones
+/// which failed and stay there.
+///
+/// Asset types without their plugin, such as sounds when the audio is
+/// disabled or everything when headless, can't be loaded and are skipped.
+fn loading_system(
+    asset_server: Res<AssetServer>, sprites: Res<SpriteAssets>, audios: Res<AudioAssets>,
+    ui: Res<UiAssets>, images: Option<Res<Assets<Image>>>,
+    sounds: Option<Res<Assets<AudioSource>>>, fonts: Option<Res<Assets<Font>>>,
+    after_loading: Res<StateAfterLoading>, mut state: ResMut<State<AppState>>,
+    mut gamestate: ResMut<State<AppGameState>>, mut texts: Query<&mut Text, With<LoadingText>>,
+    mut reported: Local<bool>,
+) {
+    let mut handles: Vec<HandleId> = Vec::new();
+    if images.is_some() {
+        handles.extend(sprites.handles());
+        handles.push(ui.ship_life.0.id());
+    }
+    if sounds.is_some() {
+        handles.extend(audios.handles());
+    }
+    if fonts.is_some() {
+        handles.push(ui.font.id());
+    }
+    handles.sort_unstable();
+    handles.dedup();
+
+    let mut loaded: usize = 0usize;
+    let mut failed: Vec<String> = Vec::new();
+    for &handle in &handles {
+        match asset_server.get_load_state(handle) {
+            LoadState::Loaded => loaded += 1usize,
+            LoadState::Failed | LoadState::Unloaded => {
+                failed.push(asset_server.get_handle_path(handle).map_or_else(
+                    || format!("{handle:?}"),
+                    |path| path.path().display().to_string(),
+                ));
+            }
+            LoadState::NotLoaded | LoadState::Loading => {}
+        }
+    }
+
+    if !failed.is_empty() {
+        if !*reported {
+            error!("Assets failed to load: {}", failed.join(", "));
+            *reported = true;
+        }
+        for mut text in &mut texts {
+            text.sections[0].value = format!("missing assets\n{}", failed.join("\n"));
+            text.sections[0].style.color = Color::rgb_u8(0xAA, 0x22, 0x22);
+        }
+    } else if loaded == handles.len() {
+        state.set(after_loading.0).unwrap();
+        if after_loading.0 == AppState::Game {
+            gamestate.set(AppGameState::Game).unwrap();
+        }
+    } else {
+        for mut text in &mut texts {
+            text.sections[0].value = format!("loading {loaded}/{}", handles.len());
+        }
+    }
+}
+
+//----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_manifest_complete() {
+        let manifest = AssetManifest::parse(include_str!("../assets/manifest.ron")).unwrap();
+        assert!(manifest.validate_complete().is_ok());
+    }
+
+    #[test]
+    fn test_skin_replaces_entries() {
+        let mut manifest = AssetManifest::default();
+        let skin = AssetManifest::parse(
+            r#"(sprites: {"player_ship": (path: "ship.png"), "meteor_big": (radius: 60.0)})"#,
+        )
+        .unwrap();
+        manifest.apply_skin(std::path::Path::new("skins/blue"), skin);
+        let ship: &SpriteEntry = manifest.sprite("player_ship");
+        assert_eq!(ship.path, "skins/blue/ship.png");
+        assert_eq!((ship.radius, ship.size), (Some(13.5f32), Some((30f32, 20f32))));
+        assert_eq!(manifest.sprite("meteor_big").path, "");
+        assert_eq!(manifest.sprite("meteor_big").radius, Some(60f32));
+        assert_eq!(manifest.sprite("laser").path, "laserRed07.png");
+    }
+
+    #[test]
+    fn test_missing_radius_rejected() {
+        let mut manifest = AssetManifest::default();
+        manifest.sprites.insert("meteor_big".to_string(), SpriteEntry::default());
+        assert!(manifest.validate_complete().is_err());
+    }
+
+    #[test]
+    fn test_invalid_sprite_rejected() {
+        let sprites =
+            ["(radius: 0.0)", "(radius: -4.0)", "(radius: NaN)", "(scale: 0.0)", "(scale: inf)"];
+        for sprite in sprites {
+            let source = format!(r#"(sprites: {{"meteor_big": {sprite}}})"#);
+            assert!(AssetManifest::parse(&source).is_err(), "{sprite}");
+        }
+        assert!(AssetManifest::parse(r#"(sprites: {"meteor_big": (scale: 2.0)})"#).is_ok());
+    }
+
+    #[test]
+    fn test_unknown_asset_rejected() {
+        assert!(AssetManifest::parse(r#"(sounds: {"laser": "laser.ogg"})"#).is_err());
+    }
+}
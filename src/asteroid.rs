@@ -1,14 +1,30 @@
-use rand::rngs::ThreadRng;
+use std::collections::VecDeque;
+
+use bevy::{
+    render::{
+        mesh::Indices,
+        render_resource::PrimitiveTopology,
+    },
+    sprite::Mesh2dHandle,
+};
+use serde::Deserialize;
 
 use crate::prelude::*;
 
 pub struct AsteroidSpawnEvent {
     pub size: AsteroidSize,
+    pub material: AsteroidMaterial,
     pub x: f32,
     pub y: f32,
     pub vx: f32,
     pub vy: f32,
     pub angvel: f32,
+    /// Mass replacing the one of the collider, kept from the body the
+    /// spawned one broke from.
+    pub mass: Option<f32>,
+    /// Piece of the asteroid the spawned one broke from, a new random
+    /// outline when `None`.
+    pub shape: Option<AsteroidShape>,
 }
 
 pub struct LaserAsteroidContactEvent {
@@ -18,7 +34,7 @@ pub struct LaserAsteroidContactEvent {
 
 //----------------------------------------------------------------
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Deserialize)]
 pub enum AsteroidSize {
     Big,
     Medium,
@@ -26,15 +42,22 @@ pub enum AsteroidSize {
 }
 
 impl AsteroidSize {
-    /// Score marked when destroying an asteroid of this size.
-    pub const fn score(self) -> u32 {
+    pub const ALL: [Self; 3] = [Self::Big, Self::Medium, Self::Small];
+
+    /// `AssetManifest` entry whose radius sizes the outline.
+    pub const fn sprite_name(self) -> &'static str {
         match self {
-            Self::Big => 40u32,
-            Self::Medium => 20u32,
-            Self::Small => 10u32,
+            Self::Big => "meteor_big",
+            Self::Medium => "meteor_med",
+            Self::Small => "meteor_small",
         }
     }
 
+    /// Score marked when destroying an asteroid of this size.
+    pub const fn score(self, scores: &ScoreConfig) -> u32 {
+        Score::Asteroid(self).score(scores)
+    }
+
     /// Defines for each if the `Asteroid` is splitted on destruction.
     /// And the spawned sub-asteroid size and radius of spawning.
     pub const fn split(self) -> Option<(Self, f32)> {
@@ -46,156 +69,561 @@ impl AsteroidSize {
     }
 }
 
+/// What an asteroid is made of, setting how many hits it takes, its tint,
+/// how it splits and how much it scores.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Deserialize)]
+pub enum AsteroidMaterial {
+    /// One hit, splits in one to three smaller rocks.
+    #[default]
+    Rock,
+    /// One hit, shatters in several small shards.
+    Ice,
+    /// Three hits, splits in one or two smaller chunks, double score.
+    Metal,
+    /// Two hits, no fragments, triple score.
+    Crystal,
+    /// One hit, blasts what's around it instead of splitting, double score.
+    Volatile,
+}
+
+/// Chances of each material for the randomly picked ones, in `Rock`, `Ice`,
+/// `Metal`, `Crystal`, `Volatile` order.
+const MATERIAL_WEIGHTS: [u32; 5] = [60u32, 20u32, 12u32, 8u32, 6u32];
+
+impl AsteroidMaterial {
+    /// Random material, mostly `Rock`.
+    pub fn random(rng: &mut impl Rng) -> Self {
+        let mut roll: u32 = rng.gen_range(0u32..MATERIAL_WEIGHTS.iter().sum());
+        for (material, weight) in
+            [Self::Rock, Self::Ice, Self::Metal, Self::Crystal, Self::Volatile]
+                .into_iter()
+                .zip(MATERIAL_WEIGHTS)
+        {
+            if roll < weight {
+                return material;
+            }
+            roll -= weight;
+        }
+        Self::Rock
+    }
+
+    /// Laser hits taken before breaking.
+    pub const fn hit_points(self) -> u32 {
+        match self {
+            Self::Rock | Self::Ice | Self::Volatile => 1u32,
+            Self::Metal => 3u32,
+            Self::Crystal => 2u32,
+        }
+    }
+
+    pub const fn score_multiplier(self) -> u32 {
+        match self {
+            Self::Rock | Self::Ice => 1u32,
+            Self::Metal | Self::Volatile => 2u32,
+            Self::Crystal => 3u32,
+        }
+    }
+
+    /// Sprite color at full hit points.
+    pub const fn tint(self) -> Color {
+        match self {
+            Self::Rock => Color::WHITE,
+            Self::Ice => Color::rgb(0.6f32, 0.85f32, 1f32),
+            Self::Metal => Color::rgb(0.55f32, 0.6f32, 0.65f32),
+            Self::Crystal => Color::rgb(0.9f32, 0.5f32, 1f32),
+            Self::Volatile => Color::rgb(1f32, 0.55f32, 0.2f32),
+        }
+    }
+
+    /// Size and spawning radius of the fragments of a broken asteroid of
+    /// `size`, which are of the same material.
+    pub const fn split(self, size: AsteroidSize) -> Option<(AsteroidSize, f32)> {
+        match self {
+            Self::Rock | Self::Metal => size.split(),
+            Self::Ice => match size {
+                AsteroidSize::Big => Some((AsteroidSize::Small, 10f32)),
+                AsteroidSize::Medium => Some((AsteroidSize::Small, 5f32)),
+                AsteroidSize::Small => None,
+            },
+            Self::Crystal | Self::Volatile => None,
+        }
+    }
+
+    /// Range of the number of fragments.
+    pub const fn fragments(self) -> Range<u8> {
+        match self {
+            Self::Rock => 1u8..4u8,
+            Self::Ice => 2u8..5u8,
+            Self::Metal => 1u8..3u8,
+            Self::Crystal | Self::Volatile => 0u8..1u8,
+        }
+    }
+}
+
 //----------------------------------------------------------------
 
+/// Color of an asteroid right after a hit it survived.
+const HIT_FLASH_COLOR: Color = Color::rgb(1f32, 0.45f32, 0.35f32);
+/// Seconds an asteroid stays in `HIT_FLASH_COLOR`.
+const HIT_FLASH_TIME: f32 = 0.1f32;
+
 #[derive(Component)]
 pub struct Asteroid {
     pub size: AsteroidSize,
+    pub material: AsteroidMaterial,
+    /// Laser hits left before breaking.
+    pub hit_points: u32,
 }
 
-//----------------------------------------------------------------
+impl Asteroid {
+    pub const fn new(size: AsteroidSize, material: AsteroidMaterial) -> Self {
+        Self { size, material, hit_points: material.hit_points() }
+    }
 
-pub struct AsteroidPlugin;
+    /// Score marked when destroying this asteroid.
+    pub const fn score(&self, scores: &ScoreConfig) -> u32 {
+        self.size.score(scores) * self.material.score_multiplier()
+    }
 
-impl Plugin for AsteroidPlugin {
-    fn build(&self, app: &mut App) {
-        app.add_event::<AsteroidSpawnEvent>()
-            .add_event::<LaserAsteroidContactEvent>()
-            .add_system_set(
-                SystemSet::on_update(AppState::Game)
-                    .with_system(arena_asteroids)
-                    .with_system(spawn_asteroid_event)
-                    .with_system(asteroid_damage.after(ContactLabel)),
-            );
+    /// Tint of the material, darker the more damaged.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn tint(&self) -> Color {
+        let health: f32 = self.hit_points as f32 / self.material.hit_points() as f32;
+        let [r, g, b, a]: [f32; 4] = self.material.tint().as_rgba_f32();
+        let shade: f32 = 0.6f32.mul_add(health, 0.4f32);
+        Color::rgba(r * shade, g * shade, b * shade, a)
     }
 }
 
+/// Briefly shown on an asteroid which survived a hit.
+#[derive(Component)]
+struct HitFlash(Timer);
+
 //----------------------------------------------------------------
 
-/// * Advance the timer by `delta` seconds. Non repeating timer will clamp at
-///   duration. Repeating timer will wrap around. Will not affect paused timers.
-fn arena_asteroids(
-    time: Res<Time>, gamestate: Res<State<AppGameState>>, mut arena: ResMut<Arena>,
-    mut asteroid_spawn_events: EventWriter<AsteroidSpawnEvent>, asteroids: Query<&Asteroid>,
-) {
-    if gamestate.current() != &AppGameState::Game {
-        return; // early exit.
+/// Outline vertices per `px` of radius.
+const SHAPE_DETAIL: f32 = 0.25f32;
+/// How far the outline strays from a circle, as a share of the radius.
+const SHAPE_ROUGHNESS: f32 = 0.3f32;
+/// Width in `px` of the outline drawn along the edge.
+const OUTLINE_WIDTH: f32 = 2f32;
+/// Mesh colors, multiplied by the `Asteroid::tint`.
+const FILL_COLOR: Color = Color::rgb(0.5f32, 0.42f32, 0.36f32);
+const OUTLINE_COLOR: Color = Color::rgb(0.9f32, 0.82f32, 0.72f32);
+
+/// Outline of an asteroid, in `px` around its center of mass. Drawn as a
+/// filled and outlined mesh, and matched by its collider.
+#[derive(Debug, Clone, PartialEq, Component)]
+pub struct AsteroidShape(pub Polygon);
+
+impl AsteroidShape {
+    /// Random jagged outline of about `radius`.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn generate(rng: &mut impl Rng, radius: f32) -> Self {
+        let count: usize = ((radius * SHAPE_DETAIL) as usize).clamp(8usize, 24usize);
+        Self::centered(Polygon::jagged(rng, radius, count, SHAPE_ROUGHNESS)).1
     }
-    arena.asteroid_spawn_timer.tick(time.delta());
-    if !arena.asteroid_spawn_timer.finished() {
-        return;
+
+    /// Shape of `polygon` around its centroid, and the centroid.
+    fn centered(polygon: Polygon) -> (Vec2, Self) {
+        let centroid: Vec2 = polygon.centroid();
+        (centroid, Self(polygon.translated(centroid.neg())))
     }
-    arena.asteroid_spawn_timer.reset();
-    let n_asteroid: usize = asteroids.iter().count();
-    if matches!(n_asteroid.cmp(&MAX_ASTEROID_COUNT), Ordering::Greater) {
-        return;
+
+    /// Cut the outline of an asteroid turned by `rotation` in wedges from
+    /// its center, the first one towards `heading`, and keep `count` of them.
+    /// A single piece is a half. Pieces are unrotated and come with their
+    /// offset from the center, `None` when too thin to keep.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn split(&self, rotation: f32, heading: f32, count: usize) -> Vec<Option<(Vec2, Self)>> {
+        let outline: Polygon = self.0.rotated(rotation);
+        let wedges: f32 = count.max(2usize) as f32;
+        (0..count)
+            .map(|i| {
+                let middle: f32 = (2f32 * PI).mul_add(i as f32 / wedges, heading);
+                let half: f32 = PI / wedges;
+                outline.wedge(Vec2::ZERO, middle - half, middle + half).map(Self::centered)
+            })
+            .collect()
+    }
+
+    /// Convex parts of the outline.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn collider(&self) -> Collider {
+        let n: u32 = self.0 .0.len() as u32;
+        let edges: Vec<[u32; 2]> = (0..n).map(|i| [i, (i + 1u32) % n]).collect();
+        Collider::convex_decomposition(&self.0 .0, &edges)
     }
 
-    let duration: f32 = arena.asteroid_spawn_timer.duration().as_secs_f32();
-    let duration: f32 = (0.8f32 * duration).max(0.1f32);
-    arena.asteroid_spawn_timer.set_duration(Duration::from_secs_f32(duration));
+    /// Filled outline, with vertex colors to be tinted by a `ColorMaterial`.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn mesh(&self) -> Mesh {
+        let outer: &[Vec2] = &self.0 .0;
+        let n: u32 = outer.len() as u32;
+        // Inner edge of the outline, towards the center.
+        let inner = outer.iter().map(|point| *point - point.normalize_or_zero() * OUTLINE_WIDTH);
 
-    let mut rng: ThreadRng = thread_rng();
-    // 0: Top , 1: Left.
-    let side: u8 = rng.gen_range(0u8..2u8);
-    let (x, y): (f32, f32) = match side {
-        0u8 => {
-            (rng.gen_range((ARENA_WIDTH.neg() / 2f32)..(ARENA_WIDTH / 2f32)), ARENA_HEIGHT / 2f32)
+        let positions: Vec<[f32; 3]> = outer
+            .iter()
+            .chain(outer)
+            .copied()
+            .chain(inner)
+            .map(|point| point.extend(0f32).to_array())
+            .collect();
+        let colors: Vec<[f32; 4]> = (0..3u32 * n)
+            .map(|i| if i < n { FILL_COLOR } else { OUTLINE_COLOR }.as_rgba_f32())
+            .collect();
+        let mut indices: Vec<u32> = self.0.triangulate().into_iter().flatten().collect();
+        for i in 0..n {
+            let j: u32 = (i + 1u32) % n;
+            let (outer_i, outer_j, inner_i, inner_j) = (n + i, n + j, 2u32 * n + i, 2u32 * n + j);
+            indices.extend([outer_i, outer_j, inner_j, outer_i, inner_j, inner_i]);
         }
-        _ => (
-            ARENA_WIDTH.neg() / 2f32,
-            rng.gen_range((ARENA_HEIGHT.neg() / 2f32)..{ ARENA_HEIGHT / 2f32 }),
-        ),
-    };
-    let (rng_arena_w, rng_arena_h): (Range<f32>, Range<f32>) = (
-        (ARENA_WIDTH.neg() / 4f32)..(ARENA_WIDTH / 4f32),
-        (ARENA_HEIGHT.neg() / 4f32)..(ARENA_HEIGHT / 4f32),
-    );
-    asteroid_spawn_events.send(AsteroidSpawnEvent {
-        size: AsteroidSize::Big,
-        x,
-        y,
-        vx: rng.gen_range(rng_arena_w),
-        vy: rng.gen_range(rng_arena_h),
-        angvel: rng.gen_range(10f32.neg()..10f32),
-    });
+
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+        mesh.set_indices(Some(Indices::U32(indices)));
+        mesh
+    }
+}
+
+//----------------------------------------------------------------
+
+pub struct AsteroidPlugin;
+
+impl Plugin for AsteroidPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AsteroidSpawnEvent>()
+            .add_event::<LaserAsteroidContactEvent>()
+            .add_fixed_system_set(
+                SimulationStage::PostPhysics,
+                SystemSet::new().with_run_criteria(run_in_game).with_system(
+                    asteroid_damage
+                        .label(StepOrder::Asteroids)
+                        .after(ContactLabel)
+                        .after(StepOrder::Movement),
+                ),
+            )
+            .add_fixed_system_set(
+                SimulationStage::Spawn,
+                SystemSet::new()
+                    .with_run_criteria(run_in_game)
+                    .with_system(spawn_asteroid_event.label(StepOrder::Asteroids)),
+            )
+            .add_system_set(SystemSet::on_update(AppState::Game).with_system(hit_flash_system));
+    }
 }
 
 //----------------------------------------------------------------
 
 fn spawn_asteroid_event(
     mut commands: Commands, mut event_reader: EventReader<AsteroidSpawnEvent>,
-    handles: Res<SpriteAssets>,
+    manifest: Res<AssetManifest>, mut game_rng: ResMut<GameRng>, mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: Option<ResMut<Assets<ColorMaterial>>>,
 ) {
     for event in event_reader.iter() {
-        let (sprite_handle, radius): (Handle<Image>, f32) = match event.size {
-            AsteroidSize::Big => (handles.meteor_big.clone(), 101f32 / 2f32),
-            AsteroidSize::Medium => (handles.meteor_med.clone(), 43f32 / 2f32),
-            AsteroidSize::Small => (handles.meteor_small.clone(), 28f32 / 2f32),
-        };
-        commands.spawn((
-            SpriteBundle {
-                // No custom size, the sprite png is already at out game size.
-                transform: Transform {
-                    translation: Vec3::new(event.x, event.y, 1f32),
-                    ..default()
-                },
-                texture: sprite_handle.clone(),
-                ..default()
-            },
-            Asteroid { size: event.size },
+        // The outlines are as big as the sprites.
+        let sprite: &SpriteEntry = manifest.sprite(event.size.sprite_name());
+        let shape: AsteroidShape = event.shape.clone().unwrap_or_else(|| {
+            AsteroidShape::generate(&mut game_rng.shapes, sprite.radius() * sprite.scale())
+        });
+        let asteroid = Asteroid::new(event.size, event.material);
+
+        let mut entity = commands.spawn((
+            SpatialBundle::from_transform(Transform::from_xyz(event.x, event.y, 1f32)),
+            Mesh2dHandle(meshes.add(shape.mesh())),
+            shape.collider(),
+            shape,
             Damage { value: 1u32 },
             ForState { states: vec![AppState::Game] },
             RigidBody::Dynamic,
-            Collider::ball(radius),
+            Wrapping,
             ActiveEvents::COLLISION_EVENTS,
             Velocity { linvel: Vec2::new(event.vx, event.vy), angvel: event.angvel },
+            event.mass.map_or_else(ColliderMassProperties::default, ColliderMassProperties::Mass),
+            ReadMassProperties::default(),
+            Trajectory::default(),
         ));
+        // Not drawn headless.
+        if let Some(materials) = &mut materials {
+            entity.insert(materials.add(ColorMaterial::from(asteroid.tint())));
+        }
+        entity.insert(asteroid);
     }
 }
 
+/// Impulse of a volatile blast at `center` on a body at `position`, if in
+/// reach.
+fn blast_impulse(center: Vec2, position: Vec2) -> Option<ExternalImpulse> {
+    let offset: Vec2 = position - center;
+    let distance: f32 = offset.length();
+    (distance < VOLATILE_BLAST_RADIUS).then(|| ExternalImpulse {
+        impulse: offset.normalize_or_zero()
+            * VOLATILE_BLAST_IMPULSE
+            * (1f32 - distance / VOLATILE_BLAST_RADIUS),
+        torque_impulse: 0f32,
+    })
+}
+
+/// Push `entity` by `impulse`, unless it is despawned by then, as lasers
+/// spent and ships lost in the same step are.
+fn push(commands: &mut Commands, entity: Entity, impulse: ExternalImpulse) {
+    commands.add(move |world: &mut World| {
+        if let Some(mut entity) = world.get_entity_mut(entity) {
+            entity.insert(impulse);
+        }
+    });
+}
+
+/// Hit the asteroids touched by a laser or a guardian shot. Broken volatile
+/// asteroids blast the bodies around them, hitting the asteroids in reach in
+/// turn, breaking the guardians and costing the ship a life. Each blast leading to a break
+/// multiplies its score, guardian shots share it.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn asteroid_damage(
-    mut commands: Commands, mut arena: ResMut<Arena>,
+    mut commands: Commands, config: Res<GameConfig>, mut arena: ResMut<Arena>,
+    mut game_rng: ResMut<GameRng>,
     mut laser_asteroid_contact_events: EventReader<LaserAsteroidContactEvent>,
+    mut shot_asteroid_contact_events: EventReader<ShotAsteroidContactEvent>,
     mut explosion_spawn_events: EventWriter<SpawnExplosionEvent>,
-    mut asteroid_spawn_events: EventWriter<AsteroidSpawnEvent>, transforms: Query<&Transform>,
-    asteroids: Query<(&Asteroid, &Transform, &Velocity)>,
+    mut asteroid_spawn_events: EventWriter<AsteroidSpawnEvent>,
+    mut ship_asteroid_contact_events: EventWriter<ShipAsteroidContactEvent>,
+    mut asteroid_guardian_contact_events: EventWriter<AsteroidGuardianContactEvent>,
+    transforms: Query<&Transform>,
+    mut asteroids: Query<(
+        Entity,
+        &mut Asteroid,
+        &AsteroidShape,
+        &Transform,
+        &Velocity,
+        &ReadMassProperties,
+    )>,
+    bodies: Query<
+        (Entity, &Transform, &RigidBody, Option<&Ship>, Option<&Guardian>),
+        (Without<Asteroid>, Without<Ghost>),
+    >,
 ) {
-    for event in laser_asteroid_contact_events.iter() {
-        let laser_transform: &Transform = transforms.get(event.laser).unwrap();
-        let (asteroid, asteroid_transform, asteroid_velocity) =
-            asteroids.get(event.asteroid).unwrap();
-        arena.score += asteroid.size.score();
+    // A laser touching an asteroid and its `Ghost` hits once.
+    let mut spent_lasers: Vec<Entity> = Vec::new();
+    // Asteroids to hit, with the number of blasts which led to the hit and
+    // the impact point.
+    let mut hits: VecDeque<(Entity, u32, Vec2)> = VecDeque::new();
+    // Lasers and guardian shots with the asteroid they touched, and the
+    // divisor of the score of what they break.
+    let contacts: Vec<(Entity, Entity, u32)> = laser_asteroid_contact_events
+        .iter()
+        .map(|event| (event.laser, event.asteroid, 1u32))
+        .chain(
+            shot_asteroid_contact_events
+                .iter()
+                .map(|event| (event.shot, event.asteroid, GUARDIAN_FIRE_SCORE_DIVISOR)),
+        )
+        .collect();
 
-        {
+    for (laser, target, divisor) in contacts {
+        let broken = |entity: Entity| {
+            asteroids.get(entity).map_or(true, |(_, asteroid, ..)| asteroid.hit_points == 0u32)
+        };
+        if spent_lasers.contains(&laser) || broken(target) {
+            continue; // Broken asteroids let lasers through.
+        }
+        spent_lasers.push(laser);
+        commands.entity(laser).despawn();
+
+        let laser_transform: &Transform = transforms.get(laser).unwrap();
+        explosion_spawn_events.send(SpawnExplosionEvent {
+            kind: ExplosionKind::LaserOnAsteroid,
+            x: laser_transform.translation.x,
+            y: laser_transform.translation.y,
+        });
+
+        hits.push_back((target, 0u32, laser_transform.translation.truncate()));
+        while let Some((entity, chain, impact)) = hits.pop_front() {
+            let Ok((_, mut asteroid, shape, transform, velocity, mass_properties)) =
+                asteroids.get_mut(entity)
+            else {
+                continue;
+            };
+            if asteroid.hit_points == 0u32 {
+                continue;
+            }
+            asteroid.hit_points -= 1u32;
+            if asteroid.hit_points > 0u32 {
+                commands
+                    .entity(entity)
+                    .insert(HitFlash(Timer::from_seconds(HIT_FLASH_TIME, TimerMode::Once)));
+                continue;
+            }
+            arena.score += asteroid.score(&config.score) * (chain + 1u32) / divisor;
+            commands.entity(entity).despawn();
+
+            let (size, material): (AsteroidSize, AsteroidMaterial) =
+                (asteroid.size, asteroid.material);
+            let center: Vec2 = transform.translation.truncate();
+            split_asteroid(
+                &mut game_rng.splitting,
+                &config,
+                (size, material, shape),
+                (transform, velocity, mass_properties),
+                impact,
+                &mut asteroid_spawn_events,
+            );
+
+            if material != AsteroidMaterial::Volatile {
+                continue;
+            }
             explosion_spawn_events.send(SpawnExplosionEvent {
-                kind: ExplosionKind::LaserOnAsteroid,
-                x: laser_transform.translation.x,
-                y: laser_transform.translation.y,
+                kind: ExplosionKind::Volatile,
+                x: center.x,
+                y: center.y,
             });
-
-            if let Some((size, radius)) = asteroid.size.split() {
-                let mut rng: ThreadRng = thread_rng();
-                for _ in 0..rng.gen_range(1u8..4u8) {
-                    let x = asteroid_transform.translation.x + rng.gen_range(radius.neg()..radius);
-                    let y = asteroid_transform.translation.y + rng.gen_range(radius.neg()..radius);
-                    let vx = rng.gen_range((ARENA_WIDTH.neg() / radius)..(ARENA_WIDTH / radius));
-                    let vy = rng.gen_range((ARENA_HEIGHT.neg() / radius)..(ARENA_HEIGHT / radius));
-                    asteroid_spawn_events.send(AsteroidSpawnEvent {
-                        size,
-                        x,
-                        y,
-                        vx,
-                        vy,
-                        angvel: asteroid_velocity.angvel,
-                    });
+            for (other, asteroid, _, transform, ..) in &asteroids {
+                if asteroid.hit_points == 0u32 {
+                    continue;
+                }
+                if let Some(impulse) = blast_impulse(center, transform.translation.truncate()) {
+                    push(&mut commands, other, impulse);
+                    hits.push_back((other, chain + 1u32, center));
+                }
+            }
+            for (body, transform, rigid_body, ship, guardian) in &bodies {
+                if *rigid_body != RigidBody::Dynamic {
+                    continue;
+                }
+                if let Some(impulse) = blast_impulse(center, transform.translation.truncate()) {
+                    push(&mut commands, body, impulse);
+                    if ship.is_some() {
+                        ship_asteroid_contact_events
+                            .send(ShipAsteroidContactEvent { ship: body, asteroid: entity });
+                    }
+                    if guardian.is_some() {
+                        asteroid_guardian_contact_events.send(AsteroidGuardianContactEvent {
+                            asteroid: entity,
+                            guardian: body,
+                        });
+                    }
                 }
             }
         }
+    }
+}
+
+/// Send the fragments of a broken asteroid, cut from its shape and pushed
+/// away from the `impact` point.
+fn split_asteroid(
+    rng: &mut impl Rng, config: &GameConfig,
+    (size, material, shape): (AsteroidSize, AsteroidMaterial, &AsteroidShape),
+    (transform, velocity, mass_properties): (&Transform, &Velocity, &ReadMassProperties),
+    impact: Vec2, asteroid_spawn_events: &mut EventWriter<AsteroidSpawnEvent>,
+) {
+    let Some((size, radius)) = material.split(size) else {
+        return;
+    };
+    let center: Vec2 = transform.translation.truncate();
+    let count: u8 = rng.gen_range(material.fragments());
+    let angle: f32 = rng.gen_range(PI.neg()..PI) / f32::from(count.max(1u8));
+    let fragmentation = Fragmentation {
+        mass: mass_properties.0.mass,
+        velocity: *velocity,
+        push: impact_push(center, impact),
+        energy: config.arena.fragment_energy,
+        spacing: radius,
+    };
+    let rotation: f32 = transform.rotation.to_euler(EulerRot::ZYX).0;
+    let pieces = shape.split(rotation, fragmentation.heading(angle), usize::from(count));
+    let fragments = fragmentation.fragments(usize::from(count), angle);
 
-        commands.entity(event.laser).despawn();
-        commands.entity(event.asteroid).despawn();
+    for (fragment, piece) in fragments.into_iter().zip(pieces) {
+        let (offset, shape) =
+            piece.map_or((fragment.offset, None), |(offset, shape)| (offset, Some(shape)));
+        asteroid_spawn_events.send(AsteroidSpawnEvent {
+            size,
+            material,
+            x: center.x + offset.x,
+            y: center.y + offset.y,
+            vx: fragment.linvel.x,
+            vy: fragment.linvel.y,
+            angvel: velocity.angvel,
+            // Unknown until the body was simulated once.
+            mass: (fragment.mass > 0f32).then_some(fragment.mass),
+            shape,
+        });
+    }
+}
+
+/// Flash the asteroids which survived a hit, then shade them by damage.
+fn hit_flash_system(
+    mut commands: Commands, time: Res<Time>, mut materials: Option<ResMut<Assets<ColorMaterial>>>,
+    mut query: Query<(Entity, &Asteroid, Option<&Handle<ColorMaterial>>, &mut HitFlash)>,
+) {
+    for (entity, asteroid, handle, mut flash) in &mut query {
+        let color: Color = if flash.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<HitFlash>();
+            asteroid.tint()
+        } else {
+            HIT_FLASH_COLOR
+        };
+        // Shared with the ghosts of the asteroid.
+        let material = handle.zip(materials.as_mut()).and_then(|(h, m)| m.get_mut(h));
+        if let Some(material) = material {
+            material.color = color;
+        }
     }
 }
 
 //----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_utils::*;
+
+    #[test]
+    fn test_metal_asteroid_takes_several_hits() {
+        let mut app = headless_game(9u64);
+        let (size, material) = (AsteroidSize::Small, AsteroidMaterial::Metal);
+        spawn_asteroid(&mut app, size, material, Vec2::new(0f32, 150f32), Vec2::ZERO);
+        press(&mut app, &[PlayerAction::Fire]);
+        run_ticks(&mut app, 40u64);
+        let mut asteroids = app.world.query::<&Asteroid>();
+        let asteroid = asteroids.single(&app.world);
+        assert!(asteroid.hit_points < AsteroidMaterial::Metal.hit_points());
+        assert_eq!(app.world.resource::<Arena>().score, 0u32);
+
+        run_ticks(&mut app, 120u64);
+        assert!(asteroids.iter(&app.world).next().is_none());
+        assert_eq!(
+            app.world.resource::<Arena>().score,
+            2u32 * AsteroidSize::Small.score(&GameConfig::default().score)
+        );
+    }
+
+    #[test]
+    fn test_volatile_asteroid_chains() {
+        let mut app = headless_game(10u64);
+        for (material, x) in [(AsteroidMaterial::Volatile, 0f32), (AsteroidMaterial::Rock, 80f32)] {
+            let position = Vec2::new(x, 250f32);
+            spawn_asteroid(&mut app, AsteroidSize::Small, material, position, Vec2::ZERO);
+        }
+        // Within the blast, but clear of the asteroids, holding still and its
+        // fire.
+        spawn_guardian(&mut app, GuardianSize::Medium, Vec2::new(-100f32, 330f32), Vec2::ZERO);
+        run_ticks(&mut app, 1u64);
+        let mut guardians = app.world.query_filtered::<Entity, With<Guardian>>();
+        let guardian: Entity = guardians.single(&app.world);
+        app.world.entity_mut(guardian).remove::<(GuardianBrain, GuardianGun)>();
+        press(&mut app, &[PlayerAction::Fire]);
+        run_ticks(&mut app, 90u64);
+        let mut asteroids = app.world.query::<&Asteroid>();
+        assert!(asteroids.iter(&app.world).next().is_none());
+        // Broken up by the blast.
+        let mut guardians = app.world.query::<&Guardian>();
+        assert!(guardians.iter(&app.world).all(|guardian| guardian.size == GuardianSize::Small));
+        assert!(guardians.iter(&app.world).next().is_some());
+        // The blasted rock scores double, as much as the volatile asteroid.
+        let small: u32 = AsteroidSize::Small.score(&GameConfig::default().score);
+        assert_eq!(app.world.resource::<Arena>().score, 4u32 * small);
+        assert_eq!(ship(&mut app).map(|ship| ship.life), Some(START_LIFE));
+    }
+}
@@ -20,25 +20,41 @@ pub struct BackgroundPlugin;
 impl Plugin for BackgroundPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugin(Material2dPlugin::<BackgroundMaterial>::default())
-            .add_startup_system(spawn_background);
+            .add_startup_system(spawn_background)
+            .add_system(fit_background);
     }
 }
 
+/// Tag of the background quad, stretched over the `ArenaBounds`.
+#[derive(Component)]
+struct Background;
+
 /// Spawn a simple stretched quad that will use background shader.
 fn spawn_background(
-    mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>,
+    mut commands: Commands, bounds: Res<ArenaBounds>, mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<BackgroundMaterial>>,
 ) {
-    commands.spawn(MaterialMesh2dBundle {
-        mesh: meshes.add(Mesh::from(shape::Quad::default())).into(),
-        transform: Transform {
-            translation: Vec3::ZERO,
-            scale: Vec3::new(ARENA_WIDTH, ARENA_HEIGHT, 1f32),
+    commands.spawn((
+        MaterialMesh2dBundle {
+            mesh: meshes.add(Mesh::from(shape::Quad::default())).into(),
+            transform: Transform {
+                translation: Vec3::ZERO,
+                scale: bounds.size.extend(1f32),
+                ..default()
+            },
+            material: materials.add(BackgroundMaterial {}),
             ..default()
         },
-        material: materials.add(BackgroundMaterial {}),
-        ..default()
-    });
+        Background,
+    ));
+}
+
+fn fit_background(bounds: Res<ArenaBounds>, mut query: Query<&mut Transform, With<Background>>) {
+    if bounds.is_changed() {
+        for mut transform in &mut query {
+            transform.scale = bounds.size.extend(1f32);
+        }
+    }
 }
 
 //----------------------------------------------------------------
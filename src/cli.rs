@@ -0,0 +1,147 @@
+use std::path::PathBuf;
+
+use bevy::{
+    math::Vec2,
+    window::PresentMode,
+};
+use clap::{
+    Parser,
+    ValueEnum,
+};
+
+use crate::{
+    arena::ArenaMode,
+    config::Difficulty,
+};
+
+/// Command-line options of the game.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Parser)]
+#[command(name = "spaceshooter", about = "A minimalist single screen space shooter.")]
+pub struct Cli {
+    /// Seed of the gameplay random streams. Random when omitted, and printed
+    /// at startup so that a run can be reproduced.
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Difficulty preset, on top of the config file. Also picked in the
+    /// start menu.
+    #[arg(long, value_enum, default_value_t = Difficulty::Normal)]
+    pub difficulty: Difficulty,
+
+    /// Edges of the arena, also selectable in the start menu.
+    #[arg(long, value_enum, default_value_t = ArenaMode::Wraparound)]
+    pub arena_mode: ArenaMode,
+
+    /// Adapt spawns to how the player is doing, instead of playing the
+    /// waves.
+    #[arg(long)]
+    pub director: bool,
+
+    /// Skin pack to draw the game with, a folder of `assets/skins`.
+    #[arg(long, value_name = "NAME")]
+    pub skin: Option<String>,
+
+    /// Skip the start menu and begin a game right away.
+    #[arg(long)]
+    pub start_game: bool,
+
+    /// Width of the window and of the arena, in pixels.
+    #[arg(long, requires = "height", value_parser = parse_size)]
+    pub width: Option<f32>,
+
+    /// Height of the window and of the arena, in pixels.
+    #[arg(long, requires = "width", value_parser = parse_size)]
+    pub height: Option<f32>,
+
+    /// How frames are presented, `auto-no-vsync` to turn vsync off.
+    #[arg(long, value_enum, default_value_t = PresentModeArg::AutoVsync)]
+    pub present_mode: PresentModeArg,
+
+    /// Play without sound.
+    #[arg(long)]
+    pub no_audio: bool,
+
+    /// Draw the physics colliders.
+    #[arg(long)]
+    pub debug_render: bool,
+
+    /// Gameplay config file, instead of `assets/config.ron`.
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Record the inputs of each game to this file, the last game overwriting
+    /// the previous ones. The config isn't reloaded and the arena doesn't
+    /// follow the window meanwhile.
+    #[arg(long, value_name = "PATH", conflicts_with = "replay")]
+    pub record: Option<PathBuf>,
+
+    /// Play back a file written with `--record`, ignoring `--seed`,
+    /// `--config`, the arena and difficulty options, and the keyboard and
+    /// gamepad inputs of the ship. Recordings made in a window only play
+    /// back in a window, and headless ones headless.
+    #[arg(long, value_name = "PATH")]
+    pub replay: Option<PathBuf>,
+
+    /// Start a game without window nor audio, run this many simulation steps
+    /// as fast as possible and print the score.
+    #[arg(long, value_name = "TICKS")]
+    pub headless: Option<u64>,
+}
+
+impl Cli {
+    /// Size given with `--width` and `--height`.
+    pub fn window_size(&self) -> Option<Vec2> {
+        self.width.zip(self.height).map(|(width, height)| Vec2::new(width, height))
+    }
+}
+
+/// Window sizes must be positive, like the configured arena size.
+fn parse_size(arg: &str) -> Result<f32, String> {
+    match arg.parse::<f32>() {
+        Ok(size) if size > 0f32 && size.is_finite() => Ok(size),
+        Ok(size) => Err(format!("must be positive, got {size}")),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// `PresentMode`s selectable with `--present-mode`.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum PresentModeArg {
+    /// Vsync when supported, the default.
+    AutoVsync,
+    AutoNoVsync,
+    Fifo,
+    Immediate,
+    Mailbox,
+}
+
+impl From<PresentModeArg> for PresentMode {
+    fn from(value: PresentModeArg) -> Self {
+        match value {
+            PresentModeArg::AutoVsync => Self::AutoVsync,
+            PresentModeArg::AutoNoVsync => Self::AutoNoVsync,
+            PresentModeArg::Fifo => Self::Fifo,
+            PresentModeArg::Immediate => Self::Immediate,
+            PresentModeArg::Mailbox => Self::Mailbox,
+        }
+    }
+}
+
+//----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_size_must_be_positive() {
+        let parse = |width: &str| {
+            Cli::try_parse_from(["spaceshooter", &format!("--width={width}"), "--height=600"])
+        };
+        assert_eq!(parse("800").unwrap().window_size(), Some(Vec2::new(800f32, 600f32)));
+        assert!(parse("0").is_err());
+        assert!(parse("-800").is_err());
+        assert!(parse("NaN").is_err());
+    }
+}
@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+
+use bevy::asset::HandleId;
+
+use crate::prelude::*;
+
+//----------------------------------------------------------------
+
+/// Replace the collider a body is spawned with by the convex hull of its
+/// sprite once the image is loaded, then add its `SpriteSize`.
+///
+/// Without images, e.g. headless, bodies keep the collider they're spawned
+/// with.
+#[derive(Debug, Default, Component)]
+pub struct ColliderFromSprite;
+
+/// Convex hull of each texture, computed once. `None` for images whose
+/// format isn't supported, or fully transparent.
+#[derive(Debug, Default, Resource)]
+pub struct SpriteColliderCache(HashMap<HandleId, Option<Collider>>);
+
+//----------------------------------------------------------------
+
+pub struct CollidePlugin;
+
+impl Plugin for CollidePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpriteColliderCache>().add_fixed_system_set(
+            SimulationStage::PreStep,
+            SystemSet::new().with_run_criteria(run_in_game).with_system(collider_from_sprite),
+        );
+    }
+}
+
+//----------------------------------------------------------------
+
+fn collider_from_sprite(
+    mut commands: Commands, images: Option<Res<Assets<Image>>>,
+    mut cache: ResMut<SpriteColliderCache>,
+    query: Query<(Entity, &Handle<Image>, &Sprite), With<ColliderFromSprite>>,
+) {
+    let Some(images) = images else { return };
+    for (entity, handle, sprite) in &query {
+        let Some(image) = images.get(handle) else { continue };
+        let collider: &Option<Collider> = cache.0.entry(handle.id()).or_insert_with(|| {
+            alpha_outline(image).and_then(|points| Collider::convex_hull(&points))
+        });
+
+        let image_size: Vec2 = image.size();
+        let size: Vec2 = sprite.custom_size.unwrap_or(image_size);
+        let mut entity = commands.entity(entity);
+        entity.remove::<ColliderFromSprite>().insert(SpriteSize(size));
+        if let Some(collider) = collider {
+            entity.insert((collider.clone(), ColliderScale::Relative(size / image_size)));
+        }
+    }
+}
@@ -56,6 +56,8 @@ pub struct UiScore {}
 pub struct UiLife {
     pub min: u32,
 }
+#[derive(Component)]
+pub struct UiWave {}
 
 //laser.rs
 //----------------------------------------------------------------
@@ -75,15 +77,22 @@ pub struct DrawBlinkTimer(pub Timer);
 //----------------------------------------------------------------
 
 /// Component - Guardian Formation (per guardian).
-#[derive(Component, Clone)]
+///
+/// Members of a formation share all but their `angle`, see
+/// `FormationPlugin`.
+#[derive(Debug, Component, Clone, PartialEq)]
 pub struct GuardianFormation {
-    /// Start at x/y.
+    /// Formation the guardian is a member of.
+    pub id: u32,
+    /// Start at x/y, where the formation was formed.
     pub start: (f32, f32),
     /// Radius of circular/elliptical formation.
     pub radius: (f32, f32),
-    /// Pivot along x/y.
+    /// Pivot along x/y, relative to the player's ship if `attached`.
     pub pivot: (f32, f32),
-    /// Speed of members moving in formation.
+    /// Whether the pivot follows the player's ship, the formation shielding it.
+    pub attached: bool,
+    /// Speed of members moving in formation, in `rad/s`.
     pub speed: f32,
     /// Start angle.
     pub angle: f32, // Change pre tick.
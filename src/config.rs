@@ -1,11 +1,72 @@
 // [REFERENCE](https://github.com/cryscan/summer-jam/blob/master/src/config.rs)
 
+use std::{
+    path::{
+        Path,
+        PathBuf,
+    },
+    time::SystemTime,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
 use crate::prelude::*;
 
+//----------------------------------------------------------------
+// game_config
+
+/// Gameplay config file, relative to the `assets` folder.
+pub const CONFIG_PATH: &str = "config.ron";
+
+//----------------------------------------------------------------
+// assets
+
+/// Asset manifest, relative to the `assets` folder.
+pub const MANIFEST_PATH: &str = "manifest.ron";
+/// Folder of the skin packs, relative to the `assets` folder.
+pub const SKINS_DIR: &str = "skins";
+
 //----------------------------------------------------------------
 // asteroid
 
 pub const MAX_ASTEROID_COUNT: usize = 20usize;
+pub const ASTEROID_SPAWN_INTERVAL: f32 = 5f32;
+
+//----------------------------------------------------------------
+// blast
+
+/// Reach in `px` of the blast of a volatile asteroid.
+pub const VOLATILE_BLAST_RADIUS: f32 = 160f32;
+/// Impulse of the blast at its center, fading out to its edge.
+pub const VOLATILE_BLAST_IMPULSE: f32 = 3000f32;
+
+//----------------------------------------------------------------
+// fragment
+
+/// Kinetic energy driving the fragments of a broken body apart, per unit of
+/// its mass, in `px²/s²`.
+pub const FRAGMENT_ENERGY: f32 = 5000f32;
+/// Speed in `px/s` a hit adds to the fragments, away from the impact point.
+pub const FRAGMENT_PUSH: f32 = 40f32;
+
+//----------------------------------------------------------------
+// spawn
+
+/// Spawns keep this far from the ships, in `px`, when they can.
+pub const SAFE_RADIUS: f32 = 200f32;
+/// Seconds a marker warns about a spawn before it happens.
+pub const SPAWN_WARNING_TIME: f32 = 1f32;
+/// Share of the timed spawns entering through a portal inside the arena.
+pub const PORTAL_SHARE: f32 = 0.2f32;
+
+//----------------------------------------------------------------
+// wave
+
+/// Wave file, relative to the `assets` folder.
+pub const WAVES_PATH: &str = "waves.ron";
 
 //----------------------------------------------------------------
 // player_ship
@@ -19,11 +80,15 @@ pub const MAX_INVINCIBLE_TIME: f32 = 5f32;
 pub const ARENA_WIDTH: f32 = 1280f32;
 pub const ARENA_HEIGHT: f32 = 800f32;
 pub const ARENA_PADDING: f32 = 20f32;
+/// Thickness of the walls of a bounded arena.
+pub const WALL_THICKNESS: f32 = 20f32;
+pub const WALL_RESTITUTION: f32 = 0.8f32;
 
 //----------------------------------------------------------------
 // fire_guardian
 
 pub const MAX_GUARDIAN_COUNT: usize = 2usize;
+pub const GUARDIAN_SPAWN_INTERVAL: f32 = 8f32;
 pub const GUARDIAN_SIZE: (f32, f32) = (97f32, 97f32);
 pub const SPRITE_SCALE: f32 = 1f32;
 
@@ -33,14 +98,27 @@ pub const SPRITE_SCALE: f32 = 1f32;
 pub const GUARDIAN_RADIUS: GuardianRadius =
     GuardianRadius { big: 256f32 / 2f32, med: 158f32 / 2f32, small: 97f32 / 2f32 };
 //----------------------------------------------------------------
+// guardian_fire
 
-pub const GUARDIAN_FIRE_MIN_SPEED: f32 = 1f32;
-pub const GUARDIAN_FIRE_MAX_SPEED: f32 = 1f32;
-pub const GUARDIAN_FIRE_NORMAL_SPEED: f32 = 1f32;
-pub const GUARDIAN_FIRE_DAMP: f32 = 1f32;
+/// Speed in `px/s` below which a guardian shot fizzles out.
+pub const GUARDIAN_FIRE_MIN_SPEED: f32 = 150f32;
+/// Top speed in `px/s` of a shot, the speed of the guardian included.
+pub const GUARDIAN_FIRE_MAX_SPEED: f32 = 900f32;
+/// Speed in `px/s` of a shot, on top of the speed of the guardian.
+pub const GUARDIAN_FIRE_NORMAL_SPEED: f32 = 600f32;
+/// Share of its speed a shot keeps after a second.
+pub const GUARDIAN_FIRE_DAMP: f32 = 0.3f32;
+/// Guardians aim at asteroids at most this far above or below, in `px`.
 pub const GUARDIAN_FIRE_HIT_RANGE_VERTICAL: f32 = 144f32;
+/// Guardians aim at asteroids at most this far left or right, in `px`.
 pub const GUARDIAN_FIRE_HIT_RANGE_HORIZONTAL: f32 = 144f32;
+/// Asteroids moving away from a guardian faster than this, in `px/s`, are
+/// left alone.
 pub const GUARDIAN_FIRE_HIT_SPEED_THRESHOLD: f32 = -0f32;
+/// Seconds between two shots of a guardian.
+pub const GUARDIAN_FIRE_COOLDOWN: f32 = 0.6f32;
+/// Asteroids shot down by the guardians score this many times less.
+pub const GUARDIAN_FIRE_SCORE_DIVISOR: u32 = 2u32;
 
 //----------------------------------------------------------------
 
@@ -82,11 +160,555 @@ pub const SCREEN_Y: f32 = ARENA_HEIGHT / -2f32 + ARENA_PADDING;
 //----------------------------------------------------------------
 // game/scoring
 
-pub const ASTEROID_SCORE_HIT_POINTS_SMALL: u32 = 40u32;
+pub const ASTEROID_SCORE_HIT_POINTS_SMALL: u32 = 10u32;
 pub const ASTEROID_SCORE_HIT_POINTS_MEDIUM: u32 = 20u32;
-pub const ASTEROID_SCORE_HIT_POINTS_BIG: u32 = 10u32;
+pub const ASTEROID_SCORE_HIT_POINTS_BIG: u32 = 40u32;
 pub const GUARDIAN_SCORE_HIT_POINTS_SMALL: u32 = 40u32;
 pub const GUARDIAN_SCORE_HIT_POINTS_MEDIUM: u32 = 20u32;
 pub const GUARDIAN_SCORE_HIT_POINTS_BIG: u32 = 10u32;
 
 //----------------------------------------------------------------
+
+//----------------------------------------------------------------
+// ship & laser
+
+pub const SHIP_THRUST: f32 = 60f32;
+pub const SHIP_ROTATION_SPEED: f32 = 3f32;
+pub const SHIP_CANNON_COOLDOWN: f32 = 0.2f32;
+pub const LASER_SPEED: f32 = 500f32;
+pub const LASER_LIFETIME: f32 = 2f32;
+
+//----------------------------------------------------------------
+// difficulty
+
+/// Preset applied on top of the `GameConfig`, chosen in the start menu.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Resource, clap::ValueEnum)]
+pub enum Difficulty {
+    /// More lives, longer invincibility, fewer and slower asteroids.
+    Easy,
+    /// The config as written.
+    #[default]
+    Normal,
+    /// Fewer lives, more and faster asteroids, more points.
+    Hard,
+    /// Hard, and then some.
+    Insane,
+}
+
+/// Multipliers a `Difficulty` applies to the matching `GameConfig` values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DifficultyPreset {
+    pub start_life: f32,
+    /// Both the invincibility after a hit and its longest chain.
+    pub invincible_time: f32,
+    /// Timed asteroid and guardian spawn intervals, and the delays, intervals
+    /// and rests of the waves.
+    pub spawn_interval: f32,
+    /// Asteroid and guardian spawn caps.
+    pub max_count: f32,
+    pub asteroid_speed: f32,
+    pub score: f32,
+}
+
+impl Difficulty {
+    pub const fn preset(self) -> DifficultyPreset {
+        match self {
+            Self::Easy => DifficultyPreset {
+                start_life: 1.5f32,
+                invincible_time: 1.5f32,
+                spawn_interval: 1.5f32,
+                max_count: 0.75f32,
+                asteroid_speed: 0.8f32,
+                score: 0.5f32,
+            },
+            Self::Normal => DifficultyPreset {
+                start_life: 1f32,
+                invincible_time: 1f32,
+                spawn_interval: 1f32,
+                max_count: 1f32,
+                asteroid_speed: 1f32,
+                score: 1f32,
+            },
+            Self::Hard => DifficultyPreset {
+                start_life: 0.6f32,
+                invincible_time: 0.75f32,
+                spawn_interval: 0.7f32,
+                max_count: 1.25f32,
+                asteroid_speed: 1.2f32,
+                score: 1.5f32,
+            },
+            Self::Insane => DifficultyPreset {
+                start_life: 0.3f32,
+                invincible_time: 0.5f32,
+                spawn_interval: 0.5f32,
+                max_count: 1.5f32,
+                asteroid_speed: 1.5f32,
+                score: 2f32,
+            },
+        }
+    }
+
+    #[must_use]
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Easy => Self::Normal,
+            Self::Normal => Self::Hard,
+            Self::Hard => Self::Insane,
+            Self::Insane => Self::Easy,
+        }
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Easy => "easy",
+            Self::Normal => "normal",
+            Self::Hard => "hard",
+            Self::Insane => "insane",
+        }
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss, clippy::cast_precision_loss)]
+fn scale_count(count: u32, scale: f32) -> u32 {
+    (count as f32 * scale).round() as u32
+}
+
+//----------------------------------------------------------------
+
+/// Gameplay tuning read from `CONFIG_PATH` at startup, each value defaulting
+/// to the matching constant above.
+#[derive(Debug, Clone, Default, PartialEq, Resource, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    pub arena: ArenaConfig,
+    pub ship: ShipConfig,
+    pub laser: LaserConfig,
+    pub score: ScoreConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ArenaConfig {
+    /// Size the window opens with, see `ArenaBounds`.
+    pub width: f32,
+    pub height: f32,
+    /// Timed and wave asteroid spawns stop above this count.
+    pub max_asteroid_count: usize,
+    /// Timed and wave guardian spawns stop above this count.
+    pub max_guardian_count: usize,
+    /// Seconds before the first timed asteroid spawn, shortened after each.
+    pub asteroid_spawn_interval: f32,
+    /// Seconds before the first timed guardian spawn, shortened after each.
+    pub guardian_spawn_interval: f32,
+    /// Multiplier of the speed asteroids spawn with, timed or in waves.
+    pub asteroid_speed_scale: f32,
+    /// Spawns keep this far from the ships, in `px`, when they can.
+    pub safe_radius: f32,
+    /// Seconds a marker blinks where something is about to spawn, `0.0`
+    /// spawns without warning.
+    pub spawn_warning: f32,
+    /// Share of the timed and directed spawns entering through a portal
+    /// inside the arena rather than from an edge, from `0.0` to `1.0`.
+    pub portal_share: f32,
+    /// Kinetic energy driving the fragments of a broken asteroid or guardian
+    /// apart, per unit of its mass, in `px²/s²`.
+    pub fragment_energy: f32,
+    /// Bounciness of the walls of a bounded arena, `1.0` keeps all the
+    /// speed.
+    pub wall_restitution: f32,
+}
+
+impl Default for ArenaConfig {
+    fn default() -> Self {
+        Self {
+            width: ARENA_WIDTH,
+            height: ARENA_HEIGHT,
+            max_asteroid_count: MAX_ASTEROID_COUNT,
+            max_guardian_count: MAX_GUARDIAN_COUNT,
+            asteroid_spawn_interval: ASTEROID_SPAWN_INTERVAL,
+            guardian_spawn_interval: GUARDIAN_SPAWN_INTERVAL,
+            asteroid_speed_scale: 1f32,
+            safe_radius: SAFE_RADIUS,
+            spawn_warning: SPAWN_WARNING_TIME,
+            portal_share: PORTAL_SHARE,
+            fragment_energy: FRAGMENT_ENERGY,
+            wall_restitution: WALL_RESTITUTION,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShipConfig {
+    pub start_life: u32,
+    /// Seconds of invincibility after a hit.
+    pub invincible_time: f32,
+    /// Longest invincibility when hits keep re-arming it.
+    pub max_invincible_time: f32,
+    pub thrust: f32,
+    /// Angular velocity in `rad/s` while rotating.
+    pub rotation_speed: f32,
+    /// Seconds between two shots.
+    pub cannon_cooldown: f32,
+}
+
+impl Default for ShipConfig {
+    fn default() -> Self {
+        Self {
+            start_life: START_LIFE,
+            invincible_time: INVINCIBLE_TIME,
+            max_invincible_time: MAX_INVINCIBLE_TIME,
+            thrust: SHIP_THRUST,
+            rotation_speed: SHIP_ROTATION_SPEED,
+            cannon_cooldown: SHIP_CANNON_COOLDOWN,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LaserConfig {
+    /// Speed in `px/s`, on top of the speed of the ship.
+    pub speed: f32,
+    /// Seconds before a laser which hit nothing vanishes.
+    pub lifetime: f32,
+}
+
+impl Default for LaserConfig {
+    fn default() -> Self {
+        Self { speed: LASER_SPEED, lifetime: LASER_LIFETIME }
+    }
+}
+
+/// Points marked when destroying something, by size.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SizeScores {
+    pub big: u32,
+    pub medium: u32,
+    pub small: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScoreConfig {
+    pub asteroid: SizeScores,
+    pub guardian: SizeScores,
+}
+
+impl Default for ScoreConfig {
+    fn default() -> Self {
+        Self {
+            asteroid: SizeScores {
+                big: ASTEROID_SCORE_HIT_POINTS_BIG,
+                medium: ASTEROID_SCORE_HIT_POINTS_MEDIUM,
+                small: ASTEROID_SCORE_HIT_POINTS_SMALL,
+            },
+            guardian: SizeScores {
+                big: GUARDIAN_SCORE_HIT_POINTS_BIG,
+                medium: GUARDIAN_SCORE_HIT_POINTS_MEDIUM,
+                small: GUARDIAN_SCORE_HIT_POINTS_SMALL,
+            },
+        }
+    }
+}
+
+impl GameConfig {
+    /// Read and validate a config file.
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be read, or `parse` fails.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let source: String = std::fs::read_to_string(path)
+            .map_err(|err| format!("could not read `{}`: {err}", path.display()))?;
+        Self::parse(&source).map_err(|err| format!("`{}`: {err}", path.display()))
+    }
+
+    /// # Errors
+    ///
+    /// If `source` isn't a valid config, or has out of range values.
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let config: Self = ron::from_str(source).map_err(|err| err.to_string())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// This config, adjusted for `difficulty`.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn with_difficulty(mut self, difficulty: Difficulty) -> Self {
+        let preset: DifficultyPreset = difficulty.preset();
+        let arena: &mut ArenaConfig = &mut self.arena;
+        arena.asteroid_spawn_interval *= preset.spawn_interval;
+        arena.guardian_spawn_interval *= preset.spawn_interval;
+        for max_count in [&mut arena.max_asteroid_count, &mut arena.max_guardian_count] {
+            let count: u32 = u32::try_from(*max_count).unwrap_or(u32::MAX);
+            *max_count = scale_count(count, preset.max_count) as usize;
+        }
+        arena.asteroid_speed_scale *= preset.asteroid_speed;
+
+        let ship: &mut ShipConfig = &mut self.ship;
+        ship.start_life = scale_count(ship.start_life, preset.start_life).max(1u32);
+        ship.invincible_time *= preset.invincible_time;
+        ship.max_invincible_time *= preset.invincible_time;
+
+        for scores in [&mut self.score.asteroid, &mut self.score.guardian] {
+            for points in [&mut scores.big, &mut scores.medium, &mut scores.small] {
+                *points = scale_count(*points, preset.score);
+            }
+        }
+        self
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let positive = [
+            ("arena.width", self.arena.width),
+            ("arena.height", self.arena.height),
+            ("arena.asteroid_spawn_interval", self.arena.asteroid_spawn_interval),
+            ("arena.guardian_spawn_interval", self.arena.guardian_spawn_interval),
+            ("arena.asteroid_speed_scale", self.arena.asteroid_speed_scale),
+            ("laser.speed", self.laser.speed),
+            ("laser.lifetime", self.laser.lifetime),
+        ];
+        for (name, value) in positive {
+            if value <= 0f32 || !value.is_finite() {
+                return Err(format!("{name} must be positive, got {value}"));
+            }
+        }
+        let not_negative = [
+            ("arena.wall_restitution", self.arena.wall_restitution),
+            ("arena.safe_radius", self.arena.safe_radius),
+            ("arena.spawn_warning", self.arena.spawn_warning),
+            ("arena.fragment_energy", self.arena.fragment_energy),
+            ("ship.invincible_time", self.ship.invincible_time),
+            ("ship.max_invincible_time", self.ship.max_invincible_time),
+            ("ship.thrust", self.ship.thrust),
+            ("ship.rotation_speed", self.ship.rotation_speed),
+            ("ship.cannon_cooldown", self.ship.cannon_cooldown),
+        ];
+        for (name, value) in not_negative {
+            if value < 0f32 || !value.is_finite() {
+                return Err(format!("{name} must not be negative, got {value}"));
+            }
+        }
+        if !(0f32..=1f32).contains(&self.arena.portal_share) {
+            return Err(format!(
+                "arena.portal_share must be between 0 and 1, got {}",
+                self.arena.portal_share
+            ));
+        }
+        if self.ship.start_life == 0u32 {
+            return Err("ship.start_life must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// The `GameConfig` as loaded, before the `Difficulty` is applied.
+#[derive(Debug, Clone, Resource)]
+pub struct BaseConfig(pub GameConfig);
+
+/// Insert the `GameConfig` adjusted for the `Difficulty`, defaults unless set
+/// by the host. Picking another difficulty re-applies it to the `BaseConfig`.
+///
+/// With a `watch` path, the file is polled while the game runs and the
+/// config is reloaded and applied live whenever it changes.
+#[derive(Debug, Default)]
+pub struct GameConfigPlugin {
+    pub config: GameConfig,
+    pub difficulty: Difficulty,
+    pub watch: Option<PathBuf>,
+}
+
+impl Plugin for GameConfigPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.difficulty)
+            .insert_resource(BaseConfig(self.config.clone()))
+            .insert_resource(self.config.clone().with_difficulty(self.difficulty))
+            .add_system(adjust_config)
+            .add_system(apply_config.after(adjust_config));
+
+        if let Some(path) = &self.watch {
+            app.insert_resource(ConfigWatcher {
+                modified: modified_time(path),
+                path: path.clone(),
+                poll_timer: Timer::from_seconds(CONFIG_POLL_INTERVAL, TimerMode::Repeating),
+            })
+            .add_startup_system_to_stage(StartupStage::PostStartup, spawn_config_notice)
+            .add_system(watch_config.before(adjust_config))
+            .add_system(fade_config_notice);
+        }
+    }
+}
+
+/// Seconds between two checks of the watched config file.
+const CONFIG_POLL_INTERVAL: f32 = 0.5f32;
+/// Seconds a reload notice stays on screen.
+const CONFIG_NOTICE_TIME: f32 = 3f32;
+
+#[derive(Debug, Resource)]
+struct ConfigWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    poll_timer: Timer,
+}
+
+/// On-screen confirmation of a reload, or its error.
+#[derive(Component)]
+struct ConfigNotice {
+    timer: Timer,
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+fn spawn_config_notice(mut commands: Commands, assets: Res<UiAssets>) {
+    let mut timer = Timer::from_seconds(CONFIG_NOTICE_TIME, TimerMode::Once);
+    timer.tick(Duration::from_secs_f32(CONFIG_NOTICE_TIME));
+    commands.spawn((
+        TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect { left: Val::Px(10f32), bottom: Val::Px(10f32), ..default() },
+                ..default()
+            },
+            text: Text::from_section(
+                "",
+                TextStyle { font: assets.font.clone(), font_size: 20f32, color: Color::WHITE },
+            ),
+            visibility: Visibility::INVISIBLE,
+            ..default()
+        },
+        ConfigNotice { timer },
+    ));
+}
+
+/// Reload the config when its file changed. A broken file is reported and
+/// the current config kept.
+fn watch_config(
+    time: Res<Time>, mut watcher: ResMut<ConfigWatcher>, mut base: ResMut<BaseConfig>,
+    mut notices: Query<(&mut Text, &mut Visibility, &mut ConfigNotice)>,
+) {
+    if !watcher.poll_timer.tick(time.delta()).just_finished() {
+        return; // early exit.
+    }
+    let modified: Option<SystemTime> = modified_time(&watcher.path);
+    if modified == watcher.modified {
+        return;
+    }
+    watcher.modified = modified;
+
+    let (message, color): (String, Color) = match GameConfig::load(&watcher.path) {
+        Ok(loaded) => {
+            info!("Reloaded `{}`", watcher.path.display());
+            base.0 = loaded;
+            ("config reloaded".to_string(), Color::rgb_u8(0x00, 0xAA, 0xAA))
+        }
+        Err(err) => {
+            error!("Config not reloaded: {err}");
+            (format!("config error: {err}"), Color::rgb_u8(0xDD, 0x44, 0x22))
+        }
+    };
+    for (mut text, mut visibility, mut notice) in &mut notices {
+        text.sections[0].value.clone_from(&message);
+        text.sections[0].style.color = color;
+        visibility.is_visible = true;
+        notice.timer.reset();
+    }
+}
+
+/// Re-apply the `Difficulty` when it or the `BaseConfig` changed.
+fn adjust_config(
+    base: Res<BaseConfig>, difficulty: Res<Difficulty>, mut config: ResMut<GameConfig>,
+) {
+    if !base.is_changed() && !difficulty.is_changed() {
+        return; // early exit.
+    }
+    let adjusted: GameConfig = base.0.clone().with_difficulty(*difficulty);
+    if *config != adjusted {
+        *config = adjusted;
+    }
+}
+
+/// Push a changed config to what was built from the previous one.
+fn apply_config(
+    config: Res<GameConfig>, arena: Option<ResMut<Arena>>, mut ships: Query<&mut Ship>,
+) {
+    if !config.is_changed() {
+        return; // early exit.
+    }
+    for mut ship in &mut ships {
+        ship.thrust = config.ship.thrust;
+        ship.rotation_speed = config.ship.rotation_speed;
+        ship.cannon_timer.set_duration(Duration::from_secs_f32(config.ship.cannon_cooldown));
+    }
+    if let Some(mut arena) = arena {
+        arena
+            .asteroid_spawn_timer
+            .set_duration(Duration::from_secs_f32(config.arena.asteroid_spawn_interval));
+        arena
+            .guardian_spawn_timer
+            .set_duration(Duration::from_secs_f32(config.arena.guardian_spawn_interval));
+    }
+}
+
+fn fade_config_notice(time: Res<Time>, mut notices: Query<(&mut Visibility, &mut ConfigNotice)>) {
+    for (mut visibility, mut notice) in &mut notices {
+        if visibility.is_visible && notice.timer.tick(time.delta()).finished() {
+            visibility.is_visible = false;
+        }
+    }
+}
+
+//----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_config_matches_defaults() {
+        let config = GameConfig::parse(include_str!("../assets/config.ron")).unwrap();
+        assert_eq!(config, GameConfig::default());
+    }
+
+    #[test]
+    fn test_partial_config_keeps_defaults() {
+        let config = GameConfig::parse("(ship: (thrust: 90.0))").unwrap();
+        assert!((config.ship.thrust - 90f32).abs() < f32::EPSILON);
+        assert_eq!(config.laser, LaserConfig::default());
+    }
+
+    #[test]
+    fn test_invalid_config_rejected() {
+        assert!(GameConfig::parse("(laser: (speed: -1.0))").is_err());
+        assert!(GameConfig::parse("(ship: (start_life: 0))").is_err());
+        assert!(GameConfig::parse("(ship: (thrusts: 1.0))").is_err());
+    }
+
+    #[test]
+    fn test_hard_difficulty_keeps_a_life() {
+        let config = GameConfig::parse("(ship: (start_life: 2))").unwrap();
+        assert_eq!(config.with_difficulty(Difficulty::Hard).ship.start_life, 1u32);
+        let config = GameConfig::parse("(ship: (start_life: 1))").unwrap();
+        assert_eq!(config.with_difficulty(Difficulty::Insane).ship.start_life, 1u32);
+    }
+
+    #[test]
+    fn test_difficulty_presets_get_harder() {
+        let presets = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard, Difficulty::Insane]
+            .map(|difficulty| GameConfig::default().with_difficulty(difficulty));
+        assert_eq!(presets[1usize], GameConfig::default());
+        for pair in presets.windows(2) {
+            let (easier, harder) = (&pair[0], &pair[1]);
+            assert!(easier.ship.start_life > harder.ship.start_life);
+            assert!(easier.ship.invincible_time > harder.ship.invincible_time);
+            assert!(easier.arena.asteroid_spawn_interval > harder.arena.asteroid_spawn_interval);
+            assert!(easier.arena.max_asteroid_count < harder.arena.max_asteroid_count);
+            assert!(easier.arena.asteroid_speed_scale < harder.arena.asteroid_speed_scale);
+            assert!(easier.score.asteroid.small < harder.score.asteroid.small);
+        }
+    }
+}
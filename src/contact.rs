@@ -9,24 +9,32 @@ pub struct ContactPlugin;
 
 impl Plugin for ContactPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system_set(
-            SystemSet::on_update(AppState::Game).with_system(contact_system.label(ContactLabel)),
+        app.add_fixed_system_set(
+            SimulationStage::PostPhysics,
+            SystemSet::new()
+                .with_run_criteria(run_in_game)
+                .with_system(contact_system.label(ContactLabel)),
         );
     }
 }
 
 //----------------------------------------------------------------
 
+#[allow(clippy::too_many_arguments)]
 fn contact_system(
     mut collision_events: EventReader<CollisionEvent>,
     mut ship_asteroid_contact_events: EventWriter<ShipAsteroidContactEvent>,
     mut laser_asteroid_contact_events: EventWriter<LaserAsteroidContactEvent>,
     mut asteroid_guardian_contact_events: EventWriter<AsteroidGuardianContactEvent>,
-    ships: Query<&Ship>, lasers: Query<&Laser>, asteroids: Query<&Asteroid>,
-    guardians: Query<&Guardian>,
+    mut shot_asteroid_contact_events: EventWriter<ShotAsteroidContactEvent>, ships: Query<&Ship>,
+    lasers: Query<&Laser>, asteroids: Query<&Asteroid>, guardians: Query<&Guardian>,
+    shots: Query<&GuardianShot>, ghosts: Query<&Ghost>,
 ) {
     for event in collision_events.iter() {
         if let CollisionEvent::Started(e1, e2, _flags) = event {
+            // Contacts across the edges of the arena are with a `Ghost`.
+            let (e1, e2) = (&ghost_source(&ghosts, *e1), &ghost_source(&ghosts, *e2));
+
             if ships.get(*e1).is_ok() && asteroids.get(*e2).is_ok() {
                 ship_asteroid_contact_events
                     .send(ShipAsteroidContactEvent { ship: *e1, asteroid: *e2 });
@@ -62,6 +70,15 @@ fn contact_system(
                 asteroid_guardian_contact_events
                     .send(AsteroidGuardianContactEvent { asteroid: *e1, guardian: *e2 });
             }
+
+            if asteroids.get(*e1).is_ok() && shots.get(*e2).is_ok() {
+                shot_asteroid_contact_events
+                    .send(ShotAsteroidContactEvent { shot: *e2, asteroid: *e1 });
+            }
+            if asteroids.get(*e2).is_ok() && shots.get(*e1).is_ok() {
+                shot_asteroid_contact_events
+                    .send(ShotAsteroidContactEvent { shot: *e1, asteroid: *e2 });
+            }
         }
     }
 }
@@ -0,0 +1,314 @@
+use crate::prelude::*;
+
+/// Seconds over which lives lost, shots and hits are remembered.
+const MEMORY_TIME: f32 = 20f32;
+/// Seconds without damage after which the player counts as fully at ease.
+const CALM_TIME: f32 = 30f32;
+/// Seconds the intensity takes to catch up with the performance.
+const RESPONSE_TIME: f32 = 5f32;
+/// Intensity a game starts at.
+const START_INTENSITY: f32 = 0.25f32;
+/// Range of asteroid speed in `px/s`, before the intensity and the config.
+const ASTEROID_SPEED: (f32, f32) = (60f32, 120f32);
+/// Range of guardian speed in `px/s`.
+const GUARDIAN_SPEED: (f32, f32) = (20f32, 60f32);
+
+/// Spawns asteroids and guardians in place of the `Waves` and the timed
+/// spawns, adapting to how the player is doing. Only present when enabled,
+/// see `DirectorPlugin`.
+///
+/// The `intensity` rises while the player is accurate and unharmed and drops
+/// after lives are lost or when the arena is crowded. It sets the spawn
+/// intervals, the share of big asteroids and their speed.
+#[derive(Debug, Resource)]
+pub struct Director {
+    /// Pressure put on the player, from `0` (calm) to `1` (all out).
+    pub intensity: f32,
+    /// Lives lost recently.
+    damage: f32,
+    /// Lasers fired recently.
+    shots: f32,
+    /// Lasers which hit an asteroid recently.
+    hits: f32,
+    /// Seconds since the ship last lost a life.
+    since_damage: f32,
+    last_life: Option<u32>,
+    asteroid_timer: Timer,
+    guardian_timer: Timer,
+}
+
+impl Default for Director {
+    fn default() -> Self {
+        Self {
+            intensity: START_INTENSITY,
+            damage: 0f32,
+            shots: 0f32,
+            hits: 0f32,
+            since_damage: 0f32,
+            last_life: None,
+            asteroid_timer: Timer::from_seconds(ASTEROID_SPAWN_INTERVAL, TimerMode::Once),
+            guardian_timer: Timer::from_seconds(GUARDIAN_SPAWN_INTERVAL, TimerMode::Once),
+        }
+    }
+}
+
+impl Director {
+    /// Intensity the player's recent performance calls for, given the
+    /// `density` of asteroids, `0` for an empty arena and `1` at the cap.
+    pub fn target_intensity(&self, density: f32) -> f32 {
+        // Half accuracy until enough shots were fired.
+        let accuracy: f32 = (self.hits + 1f32) / (self.shots + 2f32);
+        let calm: f32 = (self.since_damage / CALM_TIME).min(1f32);
+        let hurt: f32 = (self.damage / 2f32).min(1f32);
+        let density: f32 = density.clamp(0f32, 1f32);
+
+        let ease: f32 = 0.4f32.mul_add(calm, 0.4f32.mul_add(accuracy, 0.2f32));
+        0.2f32.mul_add(density.neg(), 0.5f32.mul_add(hurt.neg(), ease)).clamp(0f32, 1f32)
+    }
+
+    /// Chance of a big asteroid, the rest being medium or small.
+    const fn big_share(&self) -> f32 {
+        0.6f32.mul_add(self.intensity, 0.2f32)
+    }
+
+    fn small_share(&self) -> f32 {
+        0.5f32 * (1f32 - self.intensity)
+    }
+
+    fn asteroid_interval(&self, config: &GameConfig) -> f32 {
+        config.arena.asteroid_spawn_interval * 0.8f32.mul_add(self.intensity.neg(), 1f32)
+    }
+
+    fn guardian_interval(&self, config: &GameConfig) -> f32 {
+        config.arena.guardian_spawn_interval * (1.5f32 - self.intensity)
+    }
+
+    fn asteroid_speed_scale(&self, config: &GameConfig) -> f32 {
+        config.arena.asteroid_speed_scale * 0.7f32.mul_add(self.intensity, 0.7f32)
+    }
+}
+
+//----------------------------------------------------------------
+
+/// Replace the `Waves` and the timed spawns by the `Director`, if `enabled`.
+#[derive(Debug, Default)]
+pub struct DirectorPlugin {
+    pub enabled: bool,
+}
+
+impl Plugin for DirectorPlugin {
+    fn build(&self, app: &mut App) {
+        if !self.enabled {
+            return;
+        }
+        app.init_resource::<Director>()
+            .add_system_set(
+                SystemSet::on_enter(AppState::Game)
+                    .with_system(reset_director)
+                    .with_system(director_text_spawn),
+            )
+            .add_system_set(SystemSet::on_update(AppState::Game).with_system(director_text_system))
+            .add_fixed_system_set(
+                SimulationStage::Gameplay,
+                SystemSet::new().with_run_criteria(run_in_game).with_system(
+                    // Counts the shots of this step.
+                    director_system.after(StepOrder::Ships).before(StepOrder::Asteroids),
+                ),
+            )
+            .add_fixed_system_set(
+                SimulationStage::PostPhysics,
+                SystemSet::new()
+                    .with_run_criteria(run_in_game)
+                    .with_system(director_hits.after(ContactLabel)),
+            );
+    }
+}
+
+/// Debug readout of the `Director::intensity`.
+#[derive(Component)]
+struct DirectorText;
+
+//----------------------------------------------------------------
+
+fn reset_director(mut commands: Commands, config: Res<GameConfig>) {
+    let mut director = Director::default();
+    let interval: f32 = director.asteroid_interval(&config);
+    director.asteroid_timer = Timer::from_seconds(interval, TimerMode::Once);
+    let interval: f32 = director.guardian_interval(&config);
+    director.guardian_timer = Timer::from_seconds(interval, TimerMode::Once);
+    commands.insert_resource(director);
+}
+
+#[allow(clippy::too_many_arguments, clippy::cast_precision_loss)]
+fn director_system(
+    gamestate: Res<State<AppGameState>>, config: Res<GameConfig>, mut director: ResMut<Director>,
+    mut game_rng: ResMut<GameRng>, ships: Query<&Ship>, asteroids: Query<(), With<Asteroid>>,
+    guardians: Query<(), With<Guardian>>, warnings: Query<&SpawnWarning>,
+    mut laser_spawn_events: EventReader<LaserSpawnEvent>,
+    mut spawn_orders: EventWriter<SpawnOrder>,
+) {
+    if gamestate.current() != &AppGameState::Game {
+        return; // early exit.
+    }
+
+    // Performance.
+    let fade: f32 = (TIME_STEP.neg() / MEMORY_TIME).exp();
+    director.damage *= fade;
+    director.shots = director.shots.mul_add(fade, laser_spawn_events.iter().count() as f32);
+    director.hits *= fade;
+    director.since_damage += TIME_STEP;
+
+    let life: Option<u32> = ships.iter().find(|ship| ship.player_id == 1u32).map(|ship| ship.life);
+    if let (Some(last_life), Some(life)) = (director.last_life, life) {
+        if life < last_life {
+            director.damage += (last_life - life) as f32;
+            director.since_damage = 0f32;
+        }
+    }
+    director.last_life = life;
+
+    let (pending_asteroids, pending_guardians): (usize, usize) = pending_spawns(&warnings);
+    let n_asteroid: usize = asteroids.iter().count();
+    let density: f32 = n_asteroid as f32 / config.arena.max_asteroid_count.max(1usize) as f32;
+    let target: f32 = director.target_intensity(density);
+    let intensity: f32 = director.intensity;
+    director.intensity =
+        (target - intensity).mul_add((TIME_STEP / RESPONSE_TIME).min(1f32), intensity);
+
+    // Spawns.
+    let rng = &mut game_rng.spawning;
+
+    if director.asteroid_timer.tick(fixed_delta()).finished() {
+        let interval: f32 = director.asteroid_interval(&config);
+        director.asteroid_timer = Timer::from_seconds(interval, TimerMode::Once);
+
+        if n_asteroid + pending_asteroids < config.arena.max_asteroid_count {
+            let roll: f32 = rng.gen();
+            let size: AsteroidSize = if roll < director.big_share() {
+                AsteroidSize::Big
+            } else if roll < 1f32 - director.small_share() {
+                AsteroidSize::Medium
+            } else {
+                AsteroidSize::Small
+            };
+            spawn_orders.send(SpawnOrder {
+                kind: SpawnKind::Asteroid(size, AsteroidMaterial::random(rng)),
+                edge: random_edge(rng, &config),
+                speed: rng.gen_range(ASTEROID_SPEED.0..ASTEROID_SPEED.1)
+                    * director.asteroid_speed_scale(&config),
+                angvel: rng.gen_range(3f32.neg()..3f32),
+            });
+        }
+    }
+
+    if director.guardian_timer.tick(fixed_delta()).finished() {
+        let interval: f32 = director.guardian_interval(&config);
+        director.guardian_timer = Timer::from_seconds(interval, TimerMode::Once);
+
+        if guardians.iter().count() + pending_guardians < config.arena.max_guardian_count {
+            spawn_orders.send(SpawnOrder {
+                kind: SpawnKind::Guardian(GuardianSize::Medium),
+                edge: random_edge(rng, &config),
+                speed: rng.gen_range(GUARDIAN_SPEED.0..GUARDIAN_SPEED.1),
+                angvel: rng.gen_range(1f32.neg()..1f32),
+            });
+        }
+    }
+}
+
+/// Count the hits in the step of their contact. Read a step later, they
+/// would be lost to the frames without a step.
+#[allow(clippy::cast_precision_loss)]
+fn director_hits(
+    mut director: ResMut<Director>,
+    mut laser_asteroid_contact_events: EventReader<LaserAsteroidContactEvent>,
+) {
+    director.hits += laser_asteroid_contact_events.iter().count() as f32;
+}
+
+fn director_text_spawn(mut commands: Commands, assets: Res<UiAssets>) {
+    commands.spawn((
+        TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect { left: Val::Px(10f32), top: Val::Px(70f32), ..default() },
+                ..default()
+            },
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 20f32,
+                    color: Color::rgb_u8(0x00, 0x88, 0x88),
+                },
+            ),
+            ..default()
+        },
+        DirectorText,
+        ForState { states: vec![AppState::Game] },
+    ));
+}
+
+fn director_text_system(director: Res<Director>, mut query: Query<&mut Text, With<DirectorText>>) {
+    if director.is_changed() {
+        for mut text in &mut query {
+            text.sections[0].value = format!("intensity {:.2}", director.intensity);
+        }
+    }
+}
+
+//----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_utils::*;
+
+    #[test]
+    fn test_damage_lowers_intensity() {
+        let calm = Director { shots: 10f32, hits: 8f32, since_damage: CALM_TIME, ..default() };
+        let hurt = Director { damage: 2f32, ..default() };
+        assert!(calm.target_intensity(0f32) > 0.8f32);
+        assert!(hurt.target_intensity(0f32) < 0.2f32);
+        assert!(calm.target_intensity(1f32) < calm.target_intensity(0f32));
+    }
+
+    fn director_game(seed: u64) -> App {
+        let builder = GameAppBuilder::new().headless(true).start_in_game(true).director(true);
+        let mut app = builder.seed(Some(seed)).build();
+        run_ticks(&mut app, 1u64);
+        app
+    }
+
+    #[test]
+    fn test_director_spawns_asteroids() {
+        let mut app = director_game(8u64);
+        run_ticks(&mut app, 600u64);
+        let mut asteroids = app.world.query_filtered::<(), With<Asteroid>>();
+        assert!(asteroids.iter(&app.world).count() > 0usize);
+        assert_eq!(app.world.resource::<WaveState>().counter, 0u32);
+    }
+
+    #[test]
+    fn test_intensity_follows_shots_and_damage() {
+        let intensity = |fire: bool, hurt: bool| {
+            let mut app = director_game(9u64);
+            for tick in 0..300u64 {
+                press(&mut app, if fire { &[PlayerAction::Fire] } else { &[] });
+                if hurt && tick == 60u64 {
+                    if let Some(ship) = ship(&mut app) {
+                        ship.life -= 1u32;
+                    }
+                }
+                run_ticks(&mut app, 1u64);
+            }
+            let director = app.world.resource::<Director>();
+            assert!(!fire || director.shots > 1f32);
+            director.intensity
+        };
+        let idle: f32 = intensity(false, false);
+        // Shots into the void lower the accuracy.
+        assert!(intensity(true, false) < idle);
+        assert!(intensity(false, true) < idle);
+    }
+}
@@ -5,6 +5,8 @@ pub enum ExplosionKind {
     ShipContact,
     LaserOnAsteroid,
     AsteroidOnGuardian,
+    /// Blast of a volatile asteroid.
+    Volatile,
     //GuardianOnAsteroid,
 }
 
@@ -45,19 +47,21 @@ impl Plugin for ExplosionPlugin {
 /// * `event_reader` - Event reader
 /// * `handles` - Sprite assets
 /// * `audios` - Audio assets
-/// * `audio_output` - Audio output
+/// * `audio_output` - Audio output, missing when running headless
 fn catch_explosion_event(
     mut commands: Commands, mut event_reader: EventReader<SpawnExplosionEvent>,
-    handles: Res<SpriteAssets>, audios: Res<AudioAssets>, audio_output: Res<Audio>,
+    handles: Res<SpriteAssets>, audios: Res<AudioAssets>, audio_output: Option<Res<Audio>>,
 ) {
     for event in event_reader.iter() {
-        let (texture, sound, start_size, end_scale, duration) = match event.kind {
+        let (texture, sound, start_size, end_scale, duration, color, speed) = match event.kind {
             ExplosionKind::ShipDead => (
                 handles.ship_explosion.clone(),
                 audios.ship_explosion.clone(),
                 Vec2::new(42f32, 39f32),
                 5f32,
                 1.5f32,
+                Color::WHITE,
+                1f32,
             ),
             ExplosionKind::ShipContact => (
                 handles.ship_contact.clone(),
@@ -65,6 +69,8 @@ fn catch_explosion_event(
                 Vec2::new(42f32, 39f32),
                 2f32,
                 0.5f32,
+                Color::WHITE,
+                1f32,
             ),
             ExplosionKind::LaserOnAsteroid => (
                 handles.asteroid_explosion.clone(),
@@ -72,6 +78,8 @@ fn catch_explosion_event(
                 Vec2::new(36f32, 32f32),
                 5f32,
                 1.5f32,
+                Color::WHITE,
+                1f32,
             ),
             // [ ]: Use assets for guardians.
             ExplosionKind::AsteroidOnGuardian => (
@@ -80,12 +88,25 @@ fn catch_explosion_event(
                 Vec2::new(36f32, 32f32),
                 5f32,
                 1.5f32,
+                Color::WHITE,
+                1f32,
+            ),
+            // Grows to the size of the blast, tinted orange and lower pitched so it
+            // reads apart from the death of the ship.
+            ExplosionKind::Volatile => (
+                handles.volatile_explosion.clone(),
+                audios.volatile_explosion.clone(),
+                Vec2::new(42f32, 39f32),
+                2f32 * VOLATILE_BLAST_RADIUS / 42f32,
+                0.8f32,
+                Color::rgb(1f32, 0.55f32, 0.1f32),
+                0.6f32,
             ),
         };
 
         commands.spawn((
             SpriteBundle {
-                sprite: Sprite { custom_size: Some(start_size), ..default() },
+                sprite: Sprite { color, custom_size: Some(start_size), ..default() },
                 transform: Transform {
                     translation: Vec3::new(event.x, event.y, 3f32),
                     ..default()
@@ -101,7 +122,9 @@ fn catch_explosion_event(
             ForState { states: vec![AppState::Game] },
         ));
 
-        audio_output.play(sound);
+        if let Some(audio_output) = &audio_output {
+            audio_output.play_with_settings(sound, PlaybackSettings { speed, ..default() });
+        }
     }
 }
 
@@ -126,7 +149,7 @@ fn animate_explosion(
 ) {
     let elapsed: std::time::Duration = time.delta();
 
-    for (entity, mut transform, mut explosion) in query.iter_mut() {
+    for (entity, mut transform, mut explosion) in &mut query {
         explosion.timer.tick(elapsed);
         if explosion.timer.finished() {
             commands.entity(entity).despawn();
@@ -0,0 +1,254 @@
+use std::collections::BTreeMap;
+
+use crate::prelude::*;
+
+/// Radius in `px` of the formation shielding the ship.
+const SHIELD_RADIUS: f32 = 120f32;
+/// Turn rate of the formation shielding the ship, in `rad/s`.
+const SHIELD_SPEED: f32 = 0.8f32;
+/// Range of the radii in `px` of the ellipses flown by free formations.
+const ORBIT_RADIUS: (f32, f32) = (60f32, 160f32);
+/// Range of the turn rate of free formations, in `rad/s`.
+const ORBIT_SPEED: (f32, f32) = (0.3f32, 0.8f32);
+
+/// Numbers the formations of a game.
+#[derive(Debug, Default, Resource)]
+struct Formations {
+    next_id: u32,
+}
+
+//----------------------------------------------------------------
+
+/// Fly the guardians in formations of up to `GUARDIAN_FORMATION_MEMBERS_MAX`.
+///
+/// Guardians without a `GuardianFormation`, just spawned or split from
+/// another one, join the closest formation with room left, else form a new
+/// one. The first formation shields the player's ship, orbiting it, the
+/// others circle an ellipse around a point of the arena. Members spread
+/// evenly around their formation, closing ranks when one is destroyed.
+/// Guardians intercepting an asteroid leave their place meanwhile.
+pub struct FormationPlugin;
+
+impl Plugin for FormationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Formations>()
+            .add_system_set(SystemSet::on_enter(AppState::Game).with_system(reset_formations))
+            .add_fixed_system_set(
+                SimulationStage::PostPhysics,
+                SystemSet::new()
+                    .with_run_criteria(run_unpaused)
+                    // Overrides the destination of the guardians left free.
+                    .with_system(formation_system.after(GuardianBrainLabel)),
+            );
+    }
+}
+
+//----------------------------------------------------------------
+
+fn reset_formations(mut commands: Commands) {
+    commands.insert_resource(Formations::default());
+}
+
+/// Center of the orbit of `formation`, `None` while the ship it's attached
+/// to is missing.
+fn pivot(formation: &GuardianFormation, ship: Option<Vec2>) -> Option<Vec2> {
+    let pivot: Vec2 = Vec2::from(formation.pivot);
+    if formation.attached {
+        ship.map(|ship| ship + pivot)
+    } else {
+        Some(pivot)
+    }
+}
+
+/// New formation formed at `start`, shielding the ship if `attached`. Its
+/// `angle` is left to the caller.
+fn form(
+    rng: &mut impl Rng, id: u32, start: Vec2, attached: bool, half_size: Vec2,
+) -> GuardianFormation {
+    if attached {
+        return GuardianFormation {
+            id,
+            start: start.into(),
+            radius: (SHIELD_RADIUS, SHIELD_RADIUS),
+            pivot: (0f32, 0f32),
+            attached,
+            speed: SHIELD_SPEED,
+            angle: 0f32,
+        };
+    }
+    let direction: f32 = if rng.gen() { 1f32 } else { 1f32.neg() };
+    GuardianFormation {
+        id,
+        start: start.into(),
+        radius: (
+            rng.gen_range(ORBIT_RADIUS.0..ORBIT_RADIUS.1),
+            rng.gen_range(ORBIT_RADIUS.0..ORBIT_RADIUS.1),
+        ),
+        pivot: (
+            rng.gen_range((half_size.x.neg() / 2f32)..(half_size.x / 2f32)),
+            rng.gen_range((half_size.y.neg() / 2f32)..(half_size.y / 2f32)),
+        ),
+        attached,
+        speed: direction * rng.gen_range(ORBIT_SPEED.0..ORBIT_SPEED.1),
+        angle: 0f32,
+    }
+}
+
+/// Gather the guardians in formations and move each member's destination
+/// along its orbit.
+#[allow(clippy::too_many_arguments, clippy::cast_precision_loss)]
+fn formation_system(
+    mut commands: Commands, bounds: Res<ArenaBounds>, mode: Res<ArenaMode>,
+    mut formations: ResMut<Formations>, mut game_rng: ResMut<GameRng>,
+    ships: Query<(&Ship, &Transform)>,
+    mut guardians: Query<
+        (Entity, &Transform, &mut GuardianBrain, Option<&mut GuardianFormation>),
+        With<Guardian>,
+    >,
+) {
+    let ship: Option<Vec2> = ships
+        .iter()
+        .find(|(ship, _)| ship.player_id == 1u32)
+        .map(|(_, transform)| transform.translation.truncate());
+
+    // Members of each formation, the first one leading it.
+    let mut members: BTreeMap<u32, (GuardianFormation, Vec<Entity>)> = BTreeMap::new();
+    let mut newcomers: Vec<(Entity, Vec2)> = Vec::new();
+    let mut sorted: Vec<_> = guardians.iter().collect();
+    sorted.sort_by_key(|(entity, ..)| *entity);
+    for (entity, transform, _, formation) in sorted {
+        match formation {
+            Some(formation) => {
+                members
+                    .entry(formation.id)
+                    .or_insert_with(|| (formation.clone(), Vec::new()))
+                    .1
+                    .push(entity);
+            }
+            None => newcomers.push((entity, transform.translation.truncate())),
+        }
+    }
+
+    for (guardian, position) in newcomers {
+        let closest: Option<u32> = members
+            .iter()
+            .filter(|(_, (_, entities))| entities.len() < GUARDIAN_FORMATION_MEMBERS_MAX as usize)
+            .filter_map(|(id, (formation, _))| {
+                pivot(formation, ship).map(|pivot| (*id, pivot.distance_squared(position)))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(id, _)| id);
+        let id: u32 = closest.unwrap_or_else(|| {
+            let id: u32 = formations.next_id;
+            formations.next_id += 1u32;
+            let attached: bool =
+                ship.is_some() && !members.values().any(|(formation, _)| formation.attached);
+            let mut formation: GuardianFormation =
+                form(&mut game_rng.spawning, id, position, attached, bounds.half_size());
+            // Starting where its first member is.
+            if let Some(center) = pivot(&formation, ship) {
+                let to_start: Vec2 = offset(*bounds, *mode, center, position);
+                formation.angle = to_start.y.atan2(to_start.x);
+            }
+            members.insert(id, (formation, Vec::new()));
+            id
+        });
+        if let Some((_, entities)) = members.get_mut(&id) {
+            entities.push(guardian);
+        }
+    }
+
+    let half_size: Vec2 = bounds.half_size();
+    for (leader, entities) in members.values() {
+        let angle: f32 = leader.speed.mul_add(TIME_STEP, leader.angle).rem_euclid(2f32 * PI);
+        let center: Option<Vec2> = pivot(leader, ship);
+        // The others follow the leader in their order around the pivot, none
+        // cutting across the formation to its slot.
+        let mut ordered: Vec<(f32, Entity)> = entities
+            .iter()
+            .enumerate()
+            .map(|(i, &guardian)| {
+                let around: Option<f32> = center.filter(|_| i > 0usize).and_then(|center| {
+                    let (_, transform, ..) = guardians.get(guardian).ok()?;
+                    let to_guardian: Vec2 =
+                        offset(*bounds, *mode, center, transform.translation.truncate());
+                    Some((to_guardian.y.atan2(to_guardian.x) - angle).rem_euclid(2f32 * PI))
+                });
+                (around.unwrap_or_default(), guardian)
+            })
+            .collect();
+        ordered.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+
+        for (i, &(_, guardian)) in ordered.iter().enumerate() {
+            let Ok((.., mut brain, formation)) = guardians.get_mut(guardian) else { continue };
+            let slot: f32 = 2f32.mul_add(PI * i as f32 / entities.len() as f32, angle);
+            if let Some(mut formation) = formation {
+                formation.angle = slot;
+            } else {
+                let formation = GuardianFormation { angle: slot, ..leader.clone() };
+                // Unless the newcomer gets destroyed in this step.
+                commands.add(move |world: &mut World| {
+                    if let Some(mut entity) = world.get_entity_mut(guardian) {
+                        entity.insert(formation);
+                    }
+                });
+            }
+
+            if brain.target.is_some() {
+                continue;
+            }
+            brain.destination = center.map(|center| {
+                let radius: Vec2 = Vec2::from(leader.radius);
+                let position: Vec2 = center + Vec2::new(slot.cos(), slot.sin()) * radius;
+                match *mode {
+                    ArenaMode::Wraparound => bounds.wrap(position),
+                    ArenaMode::Bounded => position.clamp(half_size.neg(), half_size),
+                }
+            });
+        }
+    }
+}
+
+//----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_utils::*;
+
+    #[test]
+    fn test_guardians_fly_in_formation() {
+        let mut app = headless_game(12u64);
+        for x in [-200f32, -100f32, 0f32, 100f32, 200f32] {
+            spawn_guardian(&mut app, GuardianSize::Small, Vec2::new(x, 150f32), Vec2::ZERO);
+        }
+        run_ticks(&mut app, 240u64);
+        let mut ships = app.world.query_filtered::<&Transform, With<Ship>>();
+        let ship: Vec2 = ships.single(&app.world).translation.truncate();
+        let mut formations = app.world.query::<(Entity, &Transform, &GuardianFormation)>();
+        let shield: Vec<(Entity, f32)> = formations
+            .iter(&app.world)
+            .filter(|(.., formation)| formation.attached)
+            .map(|(entity, transform, _)| (entity, transform.translation.truncate().distance(ship)))
+            .collect();
+        // A full shield around the ship, the last one in a formation of its own.
+        assert_eq!(shield.len(), GUARDIAN_FORMATION_MEMBERS_MAX as usize);
+        assert_eq!(formations.iter(&app.world).count(), 5usize);
+        for (_, distance) in &shield {
+            assert!((distance - 120f32).abs() < 30f32);
+        }
+
+        // The others close ranks.
+        app.world.despawn(shield[0].0);
+        run_ticks(&mut app, 1u64);
+        let mut angles: Vec<f32> = formations
+            .iter(&app.world)
+            .filter(|(.., formation)| formation.attached)
+            .map(|(.., formation)| formation.angle.rem_euclid(2f32 * PI))
+            .collect();
+        angles.sort_by(f32::total_cmp);
+        assert_eq!(angles.len(), 3usize);
+        assert!((angles[1] - angles[0] - 2f32 * PI / 3f32).abs() < 1e-3f32);
+        assert!((angles[2] - angles[1] - 2f32 * PI / 3f32).abs() < 1e-3f32);
+    }
+}
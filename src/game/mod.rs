@@ -37,4 +37,42 @@ impl Default for Trajectory {
     }
 }
 
+impl Trajectory {
+    /// Predict the path of a body at `position` moving at `velocity`, one
+    /// point every `PREDICT_TIME_STEP` from `start_time`. The body wraps
+    /// around the edges of the arena or bounces off them, following `mode`.
+    pub fn predict(
+        &mut self, start_time: f32, position: Vec2, velocity: Vec2, bounds: &ArenaBounds,
+        mode: ArenaMode,
+    ) {
+        let half_size: Vec2 = bounds.half_size();
+        let (mut position, mut velocity): (Vec2, Vec2) = (position, velocity);
+        self.start_time = start_time;
+
+        for (i, point) in self.points.iter_mut().enumerate() {
+            if i > 0usize {
+                position += velocity * PREDICT_TIME_STEP;
+            }
+            match mode {
+                ArenaMode::Wraparound => position = bounds.wrap(position),
+                ArenaMode::Bounded => {
+                    // Mirror back in what went past a wall.
+                    let over: Vec2 = position.abs() - half_size;
+                    if over.x > 0f32 {
+                        position.x -= 2f32 * over.x * position.x.signum();
+                        velocity.x = velocity.x.neg();
+                    }
+                    if over.y > 0f32 {
+                        position.y -= 2f32 * over.y * position.y.signum();
+                        velocity.y = velocity.y.neg();
+                    }
+                }
+            }
+            #[allow(clippy::cast_precision_loss)]
+            let time: f32 = (i as f32).mul_add(PREDICT_TIME_STEP, start_time);
+            *point = Point { position, velocity, time };
+        }
+    }
+}
+
 //----------------------------------------------------------------
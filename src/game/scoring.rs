@@ -8,17 +8,17 @@ pub enum Score {
 }
 
 impl Score {
-    pub const fn score(self) -> u32 {
+    pub const fn score(self, scores: &ScoreConfig) -> u32 {
         match self {
             Self::Guardian(size) => match size {
-                GuardianSize::Big => GUARDIAN_SCORE_HIT_POINTS_BIG,
-                GuardianSize::Medium => GUARDIAN_SCORE_HIT_POINTS_MEDIUM,
-                GuardianSize::Small => GUARDIAN_SCORE_HIT_POINTS_SMALL,
+                GuardianSize::Big => scores.guardian.big,
+                GuardianSize::Medium => scores.guardian.medium,
+                GuardianSize::Small => scores.guardian.small,
             },
             Self::Asteroid(size) => match size {
-                AsteroidSize::Big => ASTEROID_SCORE_HIT_POINTS_BIG,
-                AsteroidSize::Medium => ASTEROID_SCORE_HIT_POINTS_MEDIUM,
-                AsteroidSize::Small => ASTEROID_SCORE_HIT_POINTS_SMALL,
+                AsteroidSize::Big => scores.asteroid.big,
+                AsteroidSize::Medium => scores.asteroid.medium,
+                AsteroidSize::Small => scores.asteroid.small,
             },
         }
     }
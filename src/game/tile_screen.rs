@@ -9,7 +9,7 @@ pub struct TileScreen {}
 
 impl TileScreen {
     #[allow(clippy::cast_precision_loss)]
-    pub fn column_to_coord(column: usize) -> f32 {
+    pub const fn column_to_coord(column: usize) -> f32 {
         let padding = PADDING as f32;
         let column = column as f32;
 
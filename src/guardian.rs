@@ -1,3 +1,7 @@
+use std::collections::HashSet;
+
+use serde::Deserialize;
+
 use crate::prelude::*;
 
 //----------------------------------------------------------------
@@ -15,6 +19,9 @@ pub struct GuardianSpawnEvent {
     pub vx: f32,
     pub vy: f32,
     pub angvel: f32,
+    /// Mass replacing the one of the collider, kept from the body the
+    /// spawned one broke from.
+    pub mass: Option<f32>,
 }
 
 // Asteroid hits guardian. Guardian gets damaged.
@@ -25,7 +32,7 @@ pub struct AsteroidGuardianContactEvent {
 
 //----------------------------------------------------------------
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Deserialize)]
 pub enum GuardianSize {
     Big,
     Medium,
@@ -34,12 +41,8 @@ pub enum GuardianSize {
 
 impl GuardianSize {
     /// Score marked & deduced when a guardian of this size gets destroyed.
-    pub const fn score(self) -> u32 {
-        match self {
-            Self::Big => Score::Guardian(Self::Big).score(),
-            Self::Medium => Score::Guardian(Self::Medium).score(),
-            Self::Small => Score::Guardian(Self::Small).score(),
-        }
+    pub const fn score(self, scores: &ScoreConfig) -> u32 {
+        Score::Guardian(self).score(scores)
     }
 
     /// Defines for each if the `Guardian` is splitted on destruction.
@@ -69,148 +72,145 @@ impl Plugin for GuardianPlugin {
         app.add_event::<GuardianSpawnEvent>()
             .add_event::<AsteroidGuardianContactEvent>()
             // .add_event::<GuardianAsteroidContactEvent>()
-            .add_system_set(
-                SystemSet::on_update(AppState::Game)
-                    .with_system(arena_guardians)
-                    .with_system(spawn_guardian_event)
-                    .with_system(guardian_dampening_system)
-                    .with_system(guardian_damage.after(ContactLabel)),
+            .add_fixed_system_set(
+                SimulationStage::Gameplay,
+                SystemSet::new()
+                    .with_run_criteria(run_unpaused)
+                    .with_system(guardian_dampening_system),
+            )
+            .add_fixed_system_set(
+                SimulationStage::PostPhysics,
+                SystemSet::new().with_run_criteria(run_in_game).with_system(
+                    guardian_damage
+                        .label(StepOrder::Guardians)
+                        .after(ContactLabel)
+                        .after(StepOrder::Asteroids),
+                ),
+            )
+            .add_fixed_system_set(
+                SimulationStage::Spawn,
+                SystemSet::new().with_run_criteria(run_in_game).with_system(
+                    spawn_guardian_event.label(StepOrder::Guardians).after(StepOrder::Asteroids),
+                ),
             );
     }
 }
 
 //----------------------------------------------------------------
 
-fn arena_guardians(
-    time: Res<Time>, gamestate: Res<State<AppGameState>>, mut arena: ResMut<Arena>,
-    mut guardian_spawn_events: EventWriter<GuardianSpawnEvent>, guardians: Query<&Guardian>,
-) {
-    if gamestate.current() != &AppGameState::Game {
-        return;
-    }
-    arena.guardian_spawn_timer.tick(time.delta());
-    if !arena.guardian_spawn_timer.finished() {
-        return;
-    }
-    arena.guardian_spawn_timer.reset();
-    let n_guardian: usize = guardians.iter().count();
-    if matches!(n_guardian.cmp(&MAX_GUARDIAN_COUNT), Ordering::Greater) {
-        return;
-    }
-
-    let duration: f32 = arena.guardian_spawn_timer.duration().as_secs_f32();
-    let duration: f32 = (0.8f32 * duration).max(0.1f32);
-    arena.guardian_spawn_timer.set_duration(Duration::from_secs_f32(duration));
-
-    let mut rng: ThreadRng = thread_rng();
-    // 0:Top, 1:Left.
-    let side: u8 = rng.gen_range(0u8..2u8);
-    let (x, y): (f32, f32) = match side {
-        0u8 => (
-            rng.gen_range((ARENA_WIDTH.neg().div(2f32))..(ARENA_WIDTH.div(2f32))),
-            ARENA_HEIGHT.div(2f32),
-        ),
-        _ => (
-            ARENA_WIDTH.neg().div(2f32),
-            rng.gen_range((ARENA_HEIGHT.neg().div(2f32))..ARENA_HEIGHT.div(2f32)),
-        ),
-    };
-    let (rng_arena_w, rng_arena_h): (Range<f32>, Range<f32>) = (
-        (ARENA_WIDTH.neg().div(4f32))..(ARENA_WIDTH.div(4f32)),
-        (ARENA_HEIGHT.neg().div(4f32))..(ARENA_HEIGHT.div(4f32)),
-    );
-
-    guardian_spawn_events.send(GuardianSpawnEvent {
-        size: GuardianSize::Medium,
-        x,
-        y,
-        vx: rng.gen_range(rng_arena_w), // Drastically reduce velocity
-        vy: rng.gen_range(rng_arena_h), // Drastically reduce velocity
-        angvel: rng.gen_range(2f32.neg()..2f32),
-    });
-}
-
-//----------------------------------------------------------------
-
-fn guardian_dampening_system(time: Res<Time>, mut query: Query<&mut Velocity, With<Guardian>>) {
-    for mut velocity in query.iter_mut() {
-        let elapsed: f32 = time.delta_seconds();
-        velocity.angvel *= 0.01f32.powf(elapsed); //0.1f32...
-        velocity.linvel *= 0.04f32.powf(elapsed); //0.4f32...
+pub(crate) fn guardian_dampening_system(mut query: Query<&mut Velocity, With<Guardian>>) {
+    for mut velocity in &mut query {
+        velocity.angvel *= 0.01f32.powf(TIME_STEP); //0.1f32...
+        velocity.linvel *= 0.04f32.powf(TIME_STEP); //0.4f32...
     }
 }
 
 /// Match and associate sprite texture with each guardian size.
 fn spawn_guardian_event(
     mut commands: Commands, mut event_reader: EventReader<GuardianSpawnEvent>,
-    handles: Res<SpriteAssets>,
+    handles: Res<SpriteAssets>, manifest: Res<AssetManifest>,
 ) {
     for event in event_reader.iter() {
-        let (sprite_handle, radius) = match event.size {
-            GuardianSize::Big => (handles.guardian_big.clone(), GUARDIAN_RADIUS.big),
-            GuardianSize::Medium => (handles.guardian_med.clone(), GUARDIAN_RADIUS.med),
-            GuardianSize::Small => (handles.guardian_small.clone(), GUARDIAN_RADIUS.small),
+        let (sprite_handle, sprite): (Handle<Image>, &SpriteEntry) = match event.size {
+            GuardianSize::Big => (handles.guardian_big.clone(), manifest.sprite("guardian_big")),
+            GuardianSize::Medium => (handles.guardian_med.clone(), manifest.sprite("guardian_med")),
+            GuardianSize::Small => {
+                (handles.guardian_small.clone(), manifest.sprite("guardian_small"))
+            }
         };
         commands.spawn((
             SpriteBundle {
                 // No custom size, the sprite png is already at out game size.
                 transform: Transform {
-                    translation: Vec3::new(event.x.div(4f32), event.y.div(4f32), 1f32),
+                    translation: Vec3::new(event.x, event.y, 1f32),
+                    scale: sprite.transform_scale(),
                     ..default()
                 },
                 texture: sprite_handle.clone(),
                 ..default()
             },
             Guardian { size: event.size },
+            GuardianBrain::default(),
+            GuardianGun::default(),
             Damage { value: 0u32 }, // Damage to player_ship.
             ForState { states: vec![AppState::Game] },
             RigidBody::Dynamic,
             // RigidBody::Fixed,
-            Collider::ball(radius),
+            Collider::ball(sprite.radius()),
+            ColliderFromSprite,
+            Wrapping,
             ActiveEvents::COLLISION_EVENTS, // CONTACT_FORCE_EVENTS
             Velocity { linvel: Vec2::new(event.vx, event.vy), angvel: event.angvel },
             // Velocity { linvel: Vec2::ZERO, angvel: 0f32 },
+            event.mass.map_or_else(ColliderMassProperties::default, ColliderMassProperties::Mass),
+            ReadMassProperties::default(),
         ));
     }
 }
 
 fn guardian_damage(
-    mut commands: Commands, mut arena: ResMut<Arena>,
+    mut commands: Commands, config: Res<GameConfig>, mut arena: ResMut<Arena>,
+    mut game_rng: ResMut<GameRng>,
     mut asteroid_guardian_contact_event: EventReader<AsteroidGuardianContactEvent>,
     mut explosion_spawn_events: EventWriter<SpawnExplosionEvent>,
-    mut guardian_spawn_events: EventWriter<GuardianSpawnEvent>, transforms: Query<&Transform>,
-    guardians: Query<(&Guardian, &Transform, &Velocity)>,
+    mut guardian_spawn_events: EventWriter<GuardianSpawnEvent>,
+    asteroids: Query<(&Asteroid, &Transform)>,
+    guardians: Query<(&Guardian, &Transform, &Velocity, &ReadMassProperties)>,
 ) {
+    // A guardian touching two asteroids, or one and its ghost, breaks once.
+    let mut hit_guardians: HashSet<Entity> = HashSet::new();
     for event in asteroid_guardian_contact_event.iter() {
-        let asteroid_transform: &Transform =
-            transforms.get(event.asteroid).expect("should get transform events of asteroid");
-        let (guardian, guardian_transform, guardian_velocity) = guardians
+        if !hit_guardians.insert(event.guardian) {
+            continue;
+        }
+        let (asteroid, asteroid_transform): (&Asteroid, &Transform) =
+            asteroids.get(event.asteroid).expect("should get asteroid, transform from Query");
+        // Broken in this step already, by a laser or in a volatile blast
+        // which reached the guardian.
+        let broken: bool = asteroid.hit_points == 0u32;
+        let (guardian, guardian_transform, guardian_velocity, mass_properties) = guardians
             .get(event.guardian)
             .expect("should get guardian: Self, transform, velocity from Query");
 
         {
-            explosion_spawn_events.send(SpawnExplosionEvent {
-                kind: ExplosionKind::AsteroidOnGuardian,
-                x: asteroid_transform.translation.x,
-                y: asteroid_transform.translation.y,
-            });
+            if !broken {
+                explosion_spawn_events.send(SpawnExplosionEvent {
+                    kind: ExplosionKind::AsteroidOnGuardian,
+                    x: asteroid_transform.translation.x,
+                    y: asteroid_transform.translation.y,
+                });
+            }
 
             if let Some((size, radius)) = guardian.size.split() {
-                let mut rng: ThreadRng = thread_rng();
-                for _ in 0..rng.gen_range(1u8..4u8) {
+                let rng = &mut game_rng.splitting;
+                let count: u8 = rng.gen_range(1u8..4u8);
+                let angle: f32 = rng.gen_range(PI.neg()..PI) / f32::from(count);
+                let center: Vec2 = guardian_transform.translation.truncate();
+                let fragmentation = Fragmentation {
+                    mass: mass_properties.0.mass,
+                    velocity: *guardian_velocity,
+                    push: impact_push(center, asteroid_transform.translation.truncate()),
+                    energy: config.arena.fragment_energy,
+                    spacing: radius,
+                };
+                for fragment in fragmentation.fragments(usize::from(count), angle) {
                     guardian_spawn_events.send(GuardianSpawnEvent {
                         size,
-                        x: guardian_transform.translation.x + rng.gen_range(radius.neg()..radius),
-                        y: guardian_transform.translation.y + rng.gen_range(radius.neg()..radius),
-                        vx: rng.gen_range((ARENA_WIDTH.neg() / radius)..(ARENA_WIDTH / radius)),
-                        vy: rng.gen_range((ARENA_HEIGHT.neg() / radius)..(ARENA_HEIGHT / radius)),
+                        x: center.x + fragment.offset.x,
+                        y: center.y + fragment.offset.y,
+                        vx: fragment.linvel.x,
+                        vy: fragment.linvel.y,
                         angvel: guardian_velocity.angvel,
+                        // Unknown until the body was simulated once.
+                        mass: (fragment.mass > 0f32).then_some(fragment.mass),
                     });
                 }
             }
         }
 
-        commands.entity(event.asteroid).despawn();
+        if !broken {
+            commands.entity(event.asteroid).despawn();
+        }
         commands.entity(event.guardian).despawn();
     }
 }
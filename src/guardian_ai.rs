@@ -0,0 +1,224 @@
+use crate::prelude::*;
+
+/// An asteroid predicted to pass this close to the ship, in `px`, threatens
+/// it.
+const THREAT_RADIUS: f32 = 150f32;
+/// Top speed of a steered guardian, in `px/s`.
+const GUARDIAN_SPEED: f32 = 180f32;
+/// How fast a guardian matches the velocity it steers towards, in `1/s`.
+const STEERING_RESPONSE: f32 = 4f32;
+/// Distance in `px` under which a guardian slows down to its destination.
+const ARRIVAL_RADIUS: f32 = 40f32;
+/// Distance in `px` from the ship at which idle guardians keep watch.
+const GUARD_DISTANCE: f32 = 120f32;
+
+/// What a guardian is up to, decided every `AI_TIME_STEP`, see
+/// `GuardianAiPlugin`.
+#[derive(Debug, Default, Component)]
+pub struct GuardianBrain {
+    /// Asteroid threatening the ship that the guardian moves to intercept.
+    pub target: Option<Entity>,
+    /// Point the guardian steers to, `None` to drift.
+    pub destination: Option<Vec2>,
+}
+
+/// Paces the decisions of the `GuardianBrain`s.
+#[derive(Debug, Resource)]
+struct GuardianAi {
+    timer: Timer,
+}
+
+impl Default for GuardianAi {
+    fn default() -> Self {
+        Self { timer: Timer::from_seconds(AI_TIME_STEP, TimerMode::Repeating) }
+    }
+}
+
+/// Asteroid heading close to the ship.
+struct Threat {
+    asteroid: Entity,
+    /// Index in the asteroid's `Trajectory` of the closest point to the ship.
+    point: usize,
+    time: f32,
+}
+
+//----------------------------------------------------------------
+
+/// Decisions of the `GuardianBrain`s, to override them.
+#[derive(SystemLabel, Debug, Clone, Eq, PartialEq, Hash)]
+pub struct GuardianBrainLabel;
+
+/// Turn the guardians into defenders of the player's ship.
+///
+/// Every `AI_TIME_STEP`, the path of each asteroid is predicted into its
+/// `Trajectory`. Asteroids heading for the ship are intercepted by the
+/// closest free guardian, the others fly in formation.
+pub struct GuardianAiPlugin;
+
+impl Plugin for GuardianAiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GuardianAi>()
+            .add_system_set(SystemSet::on_enter(AppState::Game).with_system(reset_guardian_ai))
+            .add_fixed_system_set(
+                SimulationStage::Gameplay,
+                SystemSet::new()
+                    .with_run_criteria(run_unpaused)
+                    .with_system(guardian_steering.after(guardian_dampening_system)),
+            )
+            .add_fixed_system_set(
+                SimulationStage::PostPhysics,
+                SystemSet::new()
+                    .with_run_criteria(run_unpaused)
+                    // Once the bodies moved and wrapped around for this step.
+                    .with_system(
+                        guardian_brain.label(GuardianBrainLabel).after(StepOrder::Movement),
+                    ),
+            );
+    }
+}
+
+//----------------------------------------------------------------
+
+fn reset_guardian_ai(mut commands: Commands) {
+    commands.insert_resource(GuardianAi::default());
+}
+
+/// First point of `trajectory` closer than `THREAT_RADIUS` to where the ship
+/// will be.
+fn threat(
+    asteroid: Entity, trajectory: &Trajectory, ship: (Vec2, Vec2), bounds: ArenaBounds,
+    mode: ArenaMode,
+) -> Option<Threat> {
+    let (ship_position, ship_velocity): (Vec2, Vec2) = ship;
+    trajectory.points.iter().enumerate().find_map(|(i, point)| {
+        let elapsed: f32 = point.time - trajectory.start_time;
+        let ship_position: Vec2 = ship_position + ship_velocity * elapsed;
+        let distance: f32 = offset(bounds, mode, point.position, ship_position).length();
+        (distance < THREAT_RADIUS).then_some(Threat { asteroid, point: i, time: point.time })
+    })
+}
+
+/// Offset from `from` to `to`, across the edges of a wraparound arena.
+pub(crate) fn offset(bounds: ArenaBounds, mode: ArenaMode, from: Vec2, to: Vec2) -> Vec2 {
+    match mode {
+        ArenaMode::Wraparound => bounds.wrapped_offset(from, to),
+        ArenaMode::Bounded => to - from,
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::cast_precision_loss)]
+fn guardian_brain(
+    bounds: Res<ArenaBounds>, mode: Res<ArenaMode>, tick: Res<SimulationTick>,
+    mut ai: ResMut<GuardianAi>, ships: Query<(&Ship, &Transform, &Velocity)>,
+    mut asteroids: Query<(Entity, &Transform, &Velocity, &mut Trajectory), With<Asteroid>>,
+    mut guardians: Query<(Entity, &Transform, &mut GuardianBrain), With<Guardian>>,
+) {
+    if !ai.timer.tick(fixed_delta()).just_finished() {
+        return; // early exit.
+    }
+    let now: f32 = tick.0 as f32 * TIME_STEP;
+    let Some((_, ship_transform, ship_velocity)) =
+        ships.iter().find(|(ship, ..)| ship.player_id == 1u32)
+    else {
+        for (.., mut brain) in &mut guardians {
+            *brain = GuardianBrain::default();
+        }
+        return;
+    };
+    let ship: (Vec2, Vec2) = (ship_transform.translation.truncate(), ship_velocity.linvel);
+
+    let mut threats: Vec<Threat> = Vec::new();
+    for (entity, transform, velocity, mut trajectory) in &mut asteroids {
+        let position: Vec2 = transform.translation.truncate();
+        trajectory.predict(now, position, velocity.linvel, &bounds, *mode);
+        threats.extend(threat(entity, &trajectory, ship, *bounds, *mode));
+    }
+    // Most urgent first, in a stable order.
+    threats.sort_by(|a, b| a.time.total_cmp(&b.time).then(a.asteroid.cmp(&b.asteroid)));
+
+    let mut free: Vec<(Entity, Vec2)> = guardians
+        .iter()
+        .map(|(entity, transform, _)| (entity, transform.translation.truncate()))
+        .collect();
+    free.sort_by_key(|(entity, _)| *entity);
+
+    for threat in threats {
+        let Ok((.., trajectory)) = asteroids.get(threat.asteroid) else { continue };
+        let points: &[Point] = &trajectory.points[..=threat.point];
+        let closest = free.iter().enumerate().min_by(|(_, (_, a)), (_, (_, b))| {
+            let danger: Vec2 = points[threat.point].position;
+            let (a, b) = (offset(*bounds, *mode, *a, danger), offset(*bounds, *mode, *b, danger));
+            a.length_squared().total_cmp(&b.length_squared())
+        });
+        let Some((index, &(guardian, position))) = closest else { break };
+        free.remove(index);
+
+        // Earliest point the guardian reaches before the asteroid does,
+        // else block it where it gets dangerous.
+        let intercept: Vec2 = points
+            .iter()
+            .find(|point| {
+                let distance: f32 = offset(*bounds, *mode, position, point.position).length();
+                distance / GUARDIAN_SPEED <= point.time - now
+            })
+            .unwrap_or(&points[threat.point])
+            .position;
+        if let Ok((.., mut brain)) = guardians.get_mut(guardian) {
+            brain.target = Some(threat.asteroid);
+            brain.destination = Some(intercept);
+        }
+    }
+
+    // The others keep watch on their side of the ship, until they fly in
+    // formation, see `FormationPlugin`.
+    for (guardian, position) in free {
+        if let Ok((.., mut brain)) = guardians.get_mut(guardian) {
+            let side: Vec2 = offset(*bounds, *mode, ship.0, position).normalize_or_zero();
+            brain.target = None;
+            brain.destination = Some(ship.0 + side * GUARD_DISTANCE);
+        }
+    }
+}
+
+/// Steer the guardians to their destination, slowing down on arrival.
+fn guardian_steering(
+    bounds: Res<ArenaBounds>, mode: Res<ArenaMode>,
+    mut guardians: Query<(&Transform, &GuardianBrain, &mut Velocity)>,
+) {
+    for (transform, brain, mut velocity) in &mut guardians {
+        let Some(destination) = brain.destination else { continue };
+        let to_destination: Vec2 =
+            offset(*bounds, *mode, transform.translation.truncate(), destination);
+        let speed: f32 = GUARDIAN_SPEED * (to_destination.length() / ARRIVAL_RADIUS).min(1f32);
+        let desired: Vec2 = to_destination.normalize_or_zero() * speed;
+        velocity.linvel = velocity.linvel.damp(desired, STEERING_RESPONSE, TIME_STEP);
+    }
+}
+
+//----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_utils::*;
+
+    #[test]
+    fn test_guardian_intercepts_threat() {
+        let mut app = headless_game(11u64);
+        let (size, material) = (AsteroidSize::Medium, AsteroidMaterial::Rock);
+        spawn_asteroid(&mut app, size, material, Vec2::new(450f32, 0f32), Vec2::new(-150f32, 0f32));
+        spawn_guardian(&mut app, GuardianSize::Small, Vec2::new(0f32, 150f32), Vec2::ZERO);
+        // Unnoticed until it's predicted to pass close to the ship.
+        run_ticks(&mut app, 30u64);
+        let mut brains = app.world.query::<&GuardianBrain>();
+        assert!(brains.single(&app.world).target.is_none());
+        run_ticks(&mut app, 40u64);
+        assert!(brains.single(&app.world).target.is_some());
+
+        run_ticks(&mut app, 120u64);
+        assert_eq!(ship(&mut app).map(|ship| ship.life), Some(START_LIFE));
+        // Shot down before rammed, the last fragments drifting away.
+        let mut asteroids = app.world.query::<&Asteroid>();
+        assert!(asteroids.iter(&app.world).all(|asteroid| asteroid.size == AsteroidSize::Small));
+    }
+}
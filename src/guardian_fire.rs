@@ -0,0 +1,157 @@
+use crate::prelude::*;
+
+/// Sprite color of the guardian shots, telling them from the ship's lasers.
+const SHOT_COLOR: Color = Color::rgb(0.4f32, 1f32, 0.6f32);
+
+/// Weapon of a guardian, ready to fire once its cooldown finished.
+#[derive(Debug, Component)]
+pub struct GuardianGun {
+    cooldown: Timer,
+}
+
+impl Default for GuardianGun {
+    fn default() -> Self {
+        Self { cooldown: Timer::from_seconds(GUARDIAN_FIRE_COOLDOWN, TimerMode::Once) }
+    }
+}
+
+/// Projectile fired by a `GuardianGun`, slowing down until it fizzles out.
+#[derive(Debug, Component)]
+pub struct GuardianShot;
+
+// Guardian shot hits asteroid. Asteroid gets damaged.
+pub struct ShotAsteroidContactEvent {
+    pub shot: Entity,
+    pub asteroid: Entity,
+}
+
+//----------------------------------------------------------------
+
+/// Arm the guardians against the asteroids.
+///
+/// A guardian whose gun is ready fires at the closest asteroid within
+/// `GUARDIAN_FIRE_HIT_RANGE_HORIZONTAL` and `GUARDIAN_FIRE_HIT_RANGE_VERTICAL`
+/// of it, unless the asteroid moves away faster than
+/// `GUARDIAN_FIRE_HIT_SPEED_THRESHOLD`. Shots hit like the ship's lasers, the
+/// asteroids they break scoring `GUARDIAN_FIRE_SCORE_DIVISOR` times less.
+pub struct GuardianFirePlugin;
+
+impl Plugin for GuardianFirePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ShotAsteroidContactEvent>()
+            .add_fixed_system_set(
+                SimulationStage::Gameplay,
+                SystemSet::new().with_run_criteria(run_unpaused).with_system(guardian_shot_system),
+            )
+            .add_fixed_system_set(
+                SimulationStage::Spawn,
+                SystemSet::new()
+                    .with_run_criteria(run_unpaused)
+                    // Serialize entity spawns, whose ids depend on the order.
+                    .with_system(guardian_fire.after(spawn_laser)),
+            );
+    }
+}
+
+//----------------------------------------------------------------
+
+/// Slow the shots down, the slowest fizzling out.
+fn guardian_shot_system(
+    mut commands: Commands, mut shots: Query<(Entity, &mut Velocity), With<GuardianShot>>,
+) {
+    for (entity, mut velocity) in &mut shots {
+        velocity.linvel *= GUARDIAN_FIRE_DAMP.powf(TIME_STEP);
+        if velocity.linvel.length() < GUARDIAN_FIRE_MIN_SPEED {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Fire the guardians' guns which are ready at the closest asteroid in
+/// range, leading it by the time the shot takes to get there.
+#[allow(clippy::too_many_arguments)]
+fn guardian_fire(
+    mut commands: Commands, bounds: Res<ArenaBounds>, mode: Res<ArenaMode>,
+    handles: Res<SpriteAssets>, manifest: Res<AssetManifest>,
+    asteroids: Query<(&Transform, &Velocity, &Asteroid)>,
+    mut guardians: Query<(&Transform, &Velocity, &mut GuardianGun), With<Guardian>>,
+) {
+    let sprite: &SpriteEntry = manifest.sprite("laser");
+    let half_size: Vec2 = sprite.custom_size().unwrap_or(Vec2::new(5f32, 20f32)) / 2f32;
+    for (transform, velocity, mut gun) in &mut guardians {
+        if !gun.cooldown.tick(fixed_delta()).finished() {
+            continue;
+        }
+        let position: Vec2 = transform.translation.truncate();
+        let target: Option<(Vec2, Vec2)> = asteroids
+            .iter()
+            .filter(|(_, _, asteroid)| asteroid.hit_points > 0u32)
+            .map(|(transform, velocity, _)| {
+                let to_asteroid: Vec2 =
+                    offset(*bounds, *mode, position, transform.translation.truncate());
+                (to_asteroid, velocity.linvel)
+            })
+            .filter(|(to_asteroid, linvel)| {
+                to_asteroid.x.abs() <= GUARDIAN_FIRE_HIT_RANGE_HORIZONTAL
+                    && to_asteroid.y.abs() <= GUARDIAN_FIRE_HIT_RANGE_VERTICAL
+                    && linvel.dot(to_asteroid.normalize_or_zero())
+                        <= GUARDIAN_FIRE_HIT_SPEED_THRESHOLD
+            })
+            .min_by(|(a, _), (b, _)| a.length_squared().total_cmp(&b.length_squared()));
+        let Some((to_asteroid, linvel)) = target else { continue };
+
+        let time: f32 = to_asteroid.length() / GUARDIAN_FIRE_NORMAL_SPEED;
+        let aim: Vec2 = (to_asteroid + (linvel - velocity.linvel) * time).normalize_or_zero();
+        if aim == Vec2::ZERO {
+            continue;
+        }
+        let shot: Vec2 = (velocity.linvel + aim * GUARDIAN_FIRE_NORMAL_SPEED)
+            .clamp_length_max(GUARDIAN_FIRE_MAX_SPEED);
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: SHOT_COLOR,
+                    custom_size: Some(half_size * 2f32),
+                    ..default()
+                },
+                transform: Transform {
+                    translation: position.extend(2f32),
+                    rotation: Quat::from_rotation_arc_2d(Vec2::Y, aim),
+                    scale: sprite.transform_scale(),
+                },
+                texture: handles.laser.clone(),
+                ..default()
+            },
+            GuardianShot,
+            ForState { states: vec![AppState::Game] },
+            RigidBody::Dynamic,
+            Collider::cuboid(half_size.x, half_size.y),
+            Velocity::linear(shot),
+            Sensor,
+            ActiveEvents::COLLISION_EVENTS,
+        ));
+        gun.cooldown.reset();
+    }
+}
+
+//----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_utils::*;
+
+    #[test]
+    fn test_guardian_shoots_asteroid_down() {
+        let mut app = headless_game(13u64);
+        // Out of the ship's way, within range of the guardian.
+        let (size, material) = (AsteroidSize::Small, AsteroidMaterial::Rock);
+        spawn_asteroid(&mut app, size, material, Vec2::new(250f32, 100f32), Vec2::ZERO);
+        spawn_guardian(&mut app, GuardianSize::Small, Vec2::new(200f32, 0f32), Vec2::ZERO);
+        run_ticks(&mut app, 90u64);
+        assert!(app.world.query::<&Asteroid>().iter(&app.world).next().is_none());
+        // The kill is shared with the guardian.
+        let small: u32 = AsteroidSize::Small.score(&GameConfig::default().score);
+        assert_eq!(app.world.resource::<Arena>().score, small / GUARDIAN_FIRE_SCORE_DIVISOR);
+    }
+}
@@ -9,15 +9,21 @@ impl Plugin for HudPlugin {
         app.add_system_set(
             SystemSet::on_update(AppState::Game)
                 .with_system(hud_score_system)
-                .with_system(hud_life_system),
+                .with_system(hud_life_system)
+                .with_system(hud_wave_system),
         )
-        .add_system_set(SystemSet::on_enter(AppState::Game).with_system(hud_spawn));
+        .add_system_set(
+            SystemSet::on_enter(AppState::Game)
+                .with_system(hud_spawn)
+                .with_system(hud_wave_spawn)
+                .with_system(hud_difficulty_spawn),
+        );
     }
 }
 
 //----------------------------------------------------------------
 
-fn hud_spawn(mut commands: Commands, assets: ResMut<UiAssets>) {
+fn hud_spawn(mut commands: Commands, config: Res<GameConfig>, assets: ResMut<UiAssets>) {
     commands
         .spawn((
             NodeBundle {
@@ -78,7 +84,7 @@ fn hud_spawn(mut commands: Commands, assets: ResMut<UiAssets>) {
             ForState { states: vec![AppState::Game] },
         ))
         .with_children(|parent| {
-            for i in 1..=START_LIFE {
+            for i in 1..=config.ship.start_life {
                 parent.spawn((
                     ImageBundle {
                         style: Style {
@@ -99,9 +105,73 @@ fn hud_spawn(mut commands: Commands, assets: ResMut<UiAssets>) {
         });
 }
 
+/// Wave counter, centered at the top.
+fn hud_wave_spawn(mut commands: Commands, assets: Res<UiAssets>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    size: Size::new(Val::Percent(100f32), Val::Px(100f32)),
+                    align_items: AlignItems::FlexStart,
+                    justify_content: JustifyContent::Center,
+                    flex_direction: FlexDirection::Row,
+                    ..default()
+                },
+                ..default()
+            },
+            ForState { states: vec![AppState::Game] },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle {
+                    style: Style {
+                        margin: UiRect { top: Val::Px(20f32), ..default() },
+                        ..default()
+                    },
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: assets.font.clone(),
+                            font_size: 30f32,
+                            color: Color::rgb_u8(0x00, 0x88, 0x88),
+                        },
+                    ),
+                    ..default()
+                },
+                UiWave {},
+            ));
+        });
+}
+
+/// Difficulty of the game, in the bottom right corner.
+fn hud_difficulty_spawn(
+    mut commands: Commands, difficulty: Res<Difficulty>, assets: Res<UiAssets>,
+) {
+    commands.spawn((
+        TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect { right: Val::Px(10f32), bottom: Val::Px(10f32), ..default() },
+                ..default()
+            },
+            text: Text::from_section(
+                difficulty.name(),
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 25f32,
+                    color: Color::rgb_u8(0x00, 0x88, 0x88),
+                },
+            ),
+            ..default()
+        },
+        ForState { states: vec![AppState::Game] },
+    ));
+}
+
 fn hud_life_system(arena: Res<Arena>, mut query: Query<&mut Text, With<UiScore>>) {
     if arena.is_changed() {
-        for mut text in query.iter_mut() {
+        for mut text in &mut query {
             text.sections[0].value = format!("{}", arena.score);
         }
     }
@@ -115,7 +185,18 @@ fn hud_score_system(ship_query: Query<&Ship>, mut uilife_query: Query<(&mut Visi
         }
     }
 
-    for (mut visibility, uilife) in uilife_query.iter_mut() {
+    for (mut visibility, uilife) in &mut uilife_query {
         visibility.is_visible = life >= uilife.min;
     }
 }
+
+fn hud_wave_system(wave: Res<WaveState>, mut query: Query<&mut Text, With<UiWave>>) {
+    if wave.is_changed() {
+        for mut text in &mut query {
+            text.sections[0].value = match wave.counter {
+                0u32 => String::new(),
+                counter => format!("wave {counter}"),
+            };
+        }
+    }
+}
@@ -17,60 +17,70 @@ impl Plugin for LaserPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<LaserDespawnEvent>()
             .add_event::<LaserSpawnEvent>()
-            .add_system_set(
-                SystemSet::on_update(AppState::Game)
-                    .with_system(laser_timeout_system)
-                    .with_system(spawn_laser),
+            .add_fixed_system_set(
+                SimulationStage::Gameplay,
+                SystemSet::new().with_run_criteria(run_in_game).with_system(laser_timeout_system),
             )
-            .add_system_to_stage(CoreStage::PostUpdate, laser_timeout_system);
+            .add_fixed_system_set(
+                SimulationStage::Spawn,
+                SystemSet::new()
+                    .with_run_criteria(run_in_game)
+                    // Serialize entity spawns, whose ids depend on the order.
+                    .with_system(spawn_laser.after(StepOrder::Guardians)),
+            );
     }
 }
 
 //----------------------------------------------------------------
 
-fn spawn_laser(
-    mut commands: Commands, mut laser_spawn_events: EventReader<LaserSpawnEvent>,
-    handles: Res<SpriteAssets>, audios: Res<AudioAssets>, audio_output: Res<Audio>,
+pub(crate) fn spawn_laser(
+    mut commands: Commands, config: Res<GameConfig>,
+    mut laser_spawn_events: EventReader<LaserSpawnEvent>, handles: Res<SpriteAssets>,
+    manifest: Res<AssetManifest>, audios: Res<AudioAssets>, audio_output: Option<Res<Audio>>,
 ) {
+    let sprite: &SpriteEntry = manifest.sprite("laser");
+    let half_size: Vec2 = sprite.custom_size().unwrap_or(Vec2::new(5f32, 20f32)) / 2f32;
     for spawn_event in laser_spawn_events.iter() {
         let transform: Transform = spawn_event.transform;
         let velocity = Velocity::linear(
             (spawn_event.velocity.linvel * Vec2::Y)
-                + Vec3::truncate(transform.rotation * Vec3::Y * 500f32),
+                + Vec3::truncate(transform.rotation * Vec3::Y * config.laser.speed),
         );
         commands.spawn((
             SpriteBundle {
-                sprite: Sprite { custom_size: Some(Vec2::new(5f32, 20f32)), ..default() },
+                sprite: Sprite { custom_size: Some(half_size * 2f32), ..default() },
                 transform: Transform {
                     translation: Vec3::new(transform.translation.x, transform.translation.y, 2f32),
                     rotation: transform.rotation,
-                    ..default()
+                    scale: sprite.transform_scale(),
                 },
                 texture: handles.laser.clone(),
                 ..default()
             },
-            Laser { despawn_timer: Timer::from_seconds(2f32, TimerMode::Once) },
+            Laser { despawn_timer: Timer::from_seconds(config.laser.lifetime, TimerMode::Once) },
             ForState { states: vec![AppState::Game] },
             RigidBody::Dynamic,
-            Collider::cuboid(2.5f32, 10f32),
+            Collider::cuboid(half_size.x, half_size.y),
             velocity,
             Sensor,
             ActiveEvents::COLLISION_EVENTS,
         ));
 
-        audio_output.play(audios.laser_trigger.clone());
+        if let Some(audio_output) = &audio_output {
+            audio_output.play(audios.laser_trigger.clone());
+        }
     }
 }
 
 //----------------------------------------------------------------
 
 fn laser_timeout_system(
-    mut commands: Commands, time: Res<Time>, gamestate: Res<State<AppGameState>>,
+    mut commands: Commands, gamestate: Res<State<AppGameState>>,
     mut query: Query<(Entity, &mut Laser)>,
 ) {
     if gamestate.current() == &AppGameState::Game {
-        for (entity, mut laser) in query.iter_mut() {
-            laser.despawn_timer.tick(time.delta());
+        for (entity, mut laser) in &mut query {
+            laser.despawn_timer.tick(fixed_delta());
 
             if laser.despawn_timer.finished() {
                 commands.entity(entity).despawn();
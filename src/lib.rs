@@ -0,0 +1,186 @@
+// https://github.com/BorisBoutillier/Kataster/blob/main/src/main.rs
+
+//! A minimalist single screen space shooter, as a set of Bevy plugins.
+//!
+//! Add `SpaceShooterPlugins` to an `App` that already has Bevy's default
+//! plugins, or use `GameAppBuilder` to get a ready to run game.
+
+// #![deny(clippy::restriction)]
+#![deny(clippy::pedantic)]
+#![warn(clippy::nursery)]
+// #![deny(clippy::cargo)]
+#![warn(dead_code)]
+#![warn(unused_variables)]
+#![warn(unused_must_use)]
+#![deny(clippy::useless_format)]
+#![allow(clippy::too_many_arguments)]
+#![allow(clippy::module_name_repetitions)]
+#![allow(clippy::needless_pass_by_value)]
+#![allow(clippy::must_use_candidate)]
+#![allow(anonymous_parameters)]
+#![allow(elided_lifetimes_in_paths)]
+// Comment this out when in production.
+#![allow(unused)]
+
+pub mod app;
+pub mod arena;
+pub mod assets;
+pub mod asteroid;
+pub mod background;
+pub mod cli;
+pub mod collide;
+pub mod components;
+pub mod config;
+pub mod contact;
+pub mod director;
+pub mod explosion;
+pub mod formation;
+pub mod game;
+pub mod guardian;
+pub mod guardian_ai;
+pub mod guardian_fire;
+pub mod hud;
+pub mod laser;
+pub mod menu;
+pub mod particle_effects;
+pub mod player_ship;
+pub mod replay;
+pub mod rng;
+pub mod simulation;
+pub mod spawn;
+pub mod state;
+pub mod utils;
+pub mod wave;
+pub mod wrap;
+
+pub mod prelude {
+    pub use std::{
+        cmp::Ordering,
+        f32::consts::PI,
+        ops::{
+            Div,
+            Neg,
+            Range,
+        },
+        time::Duration,
+    };
+
+    pub use bevy::{
+        prelude::*,
+        time::FixedTimestep,
+    };
+    pub use bevy_rapier2d::prelude::*;
+    pub use leafwing_input_manager::prelude::*;
+    pub use rand::Rng;
+
+    pub use crate::{
+        app::*,
+        arena::*,
+        assets::*,
+        asteroid::*,
+        background::*,
+        collide::*,
+        components::*,
+        config::*,
+        contact::*,
+        director::*,
+        explosion::*,
+        formation::*,
+        game::*,
+        guardian::*,
+        guardian_ai::*,
+        guardian_fire::*,
+        hud::*,
+        laser::*,
+        menu::*,
+        player_ship::*,
+        replay::*,
+        rng::*,
+        simulation::*,
+        spawn::*,
+        state::*,
+        utils::*,
+        wave::*,
+        wrap::*,
+    };
+
+    //----------------------------------------------------------------
+
+    #[derive(Resource)]
+    pub struct GuardianCount(pub usize);
+
+    //----------------------------------------------------------------
+
+    /// Drawn size of a sprite in `px`, before the scale of its `Transform`.
+    #[derive(Debug, Clone, Copy, Component)]
+    pub struct SpriteSize(pub Vec2);
+
+    impl From<(f32, f32)> for SpriteSize {
+        fn from(value: (f32, f32)) -> Self {
+            Self(Vec2::new(value.0, value.1))
+        }
+    }
+
+    //----------------------------------------------------------------
+
+    #[derive(Resource)]
+    pub struct TimeScale(pub f32);
+    impl Default for TimeScale {
+        fn default() -> Self {
+            Self(1f32)
+        }
+    }
+    impl TimeScale {
+        pub const fn reset(&mut self) {
+            self.0 = 1f32;
+        }
+    }
+    //----------------------------------------------------------------
+    /// Edge of a bounded arena.
+    #[derive(Component)]
+    pub struct Wall;
+}
+
+use bevy::app::PluginGroupBuilder;
+
+use crate::prelude::*;
+
+//----------------------------------------------------------------
+
+/// Every plugin of the game, minus the window and renderers which the host
+/// `App` provides.
+///
+/// Each plugin can be replaced or configured as in any `PluginGroup`, e.g.
+/// `SpaceShooterPlugins.build().disable::<HudPlugin>().add(MyHudPlugin)`.
+pub struct SpaceShooterPlugins;
+
+impl PluginGroup for SpaceShooterPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(GameConfigPlugin::default())
+            .add(SimulationPlugin::default())
+            .add(InputManagerPlugin::<MenuAction>::default())
+            .add(RngPlugin { seed: None })
+            .add(ReplayPlugin { record: None, playback: None })
+            .add(AssetsPlugin::default())
+            .add(ArenaPlugin::default())
+            .add(PlayerShipPlugin)
+            .add(LaserPlugin)
+            .add(GuardianPlugin)
+            .add(GuardianAiPlugin)
+            .add(GuardianFirePlugin)
+            .add(FormationPlugin)
+            .add(AsteroidPlugin)
+            .add(HudPlugin)
+            .add(MenuPlugin)
+            .add(StatesPlugin::default())
+            .add(CollidePlugin)
+            .add(ContactPlugin)
+            .add(ExplosionPlugin)
+            .add(BackgroundPlugin)
+            .add(SpawnPlugin)
+            .add(WavePlugin)
+            .add(DirectorPlugin::default())
+            .add(WrapPlugin)
+    }
+}
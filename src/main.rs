@@ -1,182 +1,69 @@
-// https://github.com/BorisBoutillier/Kataster/blob/main/src/main.rs
-
-// #![deny(clippy::restriction)]
 #![deny(clippy::pedantic)]
 #![warn(clippy::nursery)]
-// #![deny(clippy::cargo)]
-#![warn(dead_code)]
-#![warn(unused_variables)]
-#![warn(unused_must_use)]
-#![deny(clippy::useless_format)]
-#![allow(clippy::too_many_arguments)]
-#![allow(clippy::module_name_repetitions)]
-#![allow(clippy::needless_pass_by_value)]
-#![allow(anonymous_parameters)]
-#![allow(elided_lifetimes_in_paths)]
-// Comment this out when in production.
-#![allow(unused)]
-
-mod arena;
-mod assets;
-mod asteroid;
-mod background;
-mod components;
-mod config;
-mod contact;
-mod explosion;
-mod game;
-mod guardian;
-mod hud;
-mod laser;
-mod menu;
-mod particle_effects;
-mod player_ship;
-mod state;
-mod utils;
-
-mod prelude {
-    pub use std::{
-        cmp::Ordering,
-        f32::consts::PI,
-        ops::{
-            Div,
-            Neg,
-            Range,
-        },
-        time::Duration,
-    };
-
-    pub use bevy::{
-        prelude::*,
-        time::FixedTimestep,
-    };
-    pub use bevy_rapier2d::prelude::*;
-    pub use leafwing_input_manager::prelude::*;
-    pub use rand::{
-        rngs::ThreadRng,
-        thread_rng,
-        Rng,
-    };
-
-    pub use crate::{
-        arena::*,
-        assets::*,
-        asteroid::*,
-        background::*,
-        components::*,
-        config::*,
-        contact::*,
-        explosion::*,
-        game::*,
-        guardian::*,
-        hud::*,
-        laser::*,
-        menu::*,
-        player_ship::*,
-        state::*,
-        utils::*,
-    };
-
-    //----------------------------------------------------------------
-
-    #[derive(Resource)]
-    pub struct GuardianCount(pub usize);
-
-    #[derive(Component)]
-    pub struct Guardian;
 
-    //----------------------------------------------------------------
+use std::{
+    path::PathBuf,
+    process::ExitCode,
+};
 
-    #[derive(Component)]
-    pub struct SpriteSize(pub Vec2);
+use clap::Parser;
+use spaceshooter::{
+    cli::Cli,
+    prelude::*,
+};
 
-    impl From<(f32, f32)> for SpriteSize {
-        fn from(value: (f32, f32)) -> Self {
-            Self(Vec2::new(value.0, value.1))
-        }
-    }
-
-    //----------------------------------------------------------------
+//----------------------------------------------------------------
 
-    #[derive(Resource)]
-    pub struct TimeScale(pub f32);
-    impl Default for TimeScale {
-        fn default() -> Self {
-            Self(1f32)
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
         }
     }
-    impl TimeScale {
-        pub fn reset(&mut self) {
-            self.0 = 1f32;
-        }
-    }
-    //----------------------------------------------------------------
-    #[derive(Component)]
-    pub struct Wall;
 }
 
-use bevy::window::PresentMode;
-
-use crate::prelude::*;
-
-//----------------------------------------------------------------
-
-fn main() {
-    let mut app = App::new();
-
-    app.insert_resource(ClearColor(Color::rgb_u8(0, 0, 0))); // 0.01, 0.1, 0.001
-    app.add_plugins(DefaultPlugins.set(WindowPlugin {
-        window: WindowDescriptor {
-            title: "SpaceGuardian".to_string(),
-            width: ARENA_WIDTH,
-            height: ARENA_HEIGHT,
-            present_mode: PresentMode::AutoVsync,
-            ..default()
-        },
-        ..default()
-    }));
-
-    // Compute shaders are not supported on WASM.
-    #[cfg(not(target_arch = "wasm32"))]
-    {
-        app.add_plugin(particle_effects::ParticleEffectsPlugin);
+fn run(cli: Cli) -> Result<(), String> {
+    let playback: Option<Replay> = cli.replay.as_deref().map(Replay::load).transpose()?;
+    if let Some(replay) = &playback {
+        replay.check_colliders(cli.headless.is_none())?;
     }
 
-    // Enable Rapier debug renders when compile in debug mode.
-    #[cfg(debug_assertions)]
-    app.add_plugin(RapierDebugRenderPlugin::default());
-
-    app.add_plugin(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(10f32));
-    app.add_plugin(InputManagerPlugin::<MenuAction>::default());
-
-    app.add_plugin(AssetsPlugin)
-        .add_plugin(ArenaPlugin)
-        .add_plugin(PlayerShipPlugin)
-        .add_plugin(LaserPlugin)
-        .add_plugin(GuardianPlugin)
-        .add_plugin(AsteroidPlugin)
-        .add_plugin(HudPlugin)
-        .add_plugin(MenuPlugin)
-        .add_plugin(StatesPlugin)
-        .add_plugin(ContactPlugin)
-        .add_plugin(ExplosionPlugin)
-        .add_plugin(BackgroundPlugin);
-
-    app.add_state(AppState::StartMenu).add_state(AppGameState::Invalid);
-
-    app.add_startup_system(setup_camera);
-    app.add_startup_system(setup_system);
-
-    app.run();
-}
-
-pub fn setup_camera(mut commands: Commands) {
-    commands.spawn(Camera2dBundle::default());
-}
-
-pub fn setup_system(mut commands: Commands) {
-    commands.insert_resource(GuardianCount(0));
-
-    // HACK: Temporary assignment to non existing resource.
-    commands.insert_resource(WinSize { width: ARENA_WIDTH / 2f32, height: ARENA_HEIGHT / 2f32 });
+    // The bundled config file is optional, but must be valid when present.
+    let config_path: PathBuf = cli.config.clone().unwrap_or_else(|| asset_path(CONFIG_PATH));
+    let (config, watch): (GameConfig, Option<PathBuf>) =
+        if cli.config.is_some() || config_path.exists() {
+            (GameConfig::load(&config_path)?, Some(config_path))
+        } else {
+            (GameConfig::default(), None)
+        };
+
+    let builder = GameAppBuilder::new()
+        .config(config)
+        .watch_config(watch)
+        .difficulty(cli.difficulty)
+        .arena_mode(cli.arena_mode)
+        .director(cli.director)
+        .window_size(cli.window_size())
+        .skin(cli.skin)
+        .present_mode(Some(cli.present_mode.into()))
+        .mute(cli.no_audio)
+        .debug_render(cli.debug_render)
+        .headless(cli.headless.is_some())
+        .start_in_game(cli.start_game || cli.headless.is_some())
+        .seed(cli.seed)
+        .record(cli.record)
+        .playback(playback);
+
+    match cli.headless {
+        Some(ticks) => {
+            let mut app = builder.build();
+            run_ticks(&mut app, ticks);
+            let score: u32 = app.world.get_resource::<Arena>().map_or(0u32, |arena| arena.score);
+            println!("score {score} after {} ticks", app.world.resource::<SimulationTick>().0);
+        }
+        None => builder.build().run(),
+    }
+    Ok(())
 }
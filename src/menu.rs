@@ -15,8 +15,20 @@ pub enum MenuAction {
     ExitToMenu,
     /// During non-gameplay screens, quit the game.
     Quit,
+    /// In the start screen, switch to the next `ArenaMode`.
+    NextArenaMode,
+    /// In the start screen, switch to the next `Difficulty`.
+    NextDifficulty,
 }
 
+/// Start screen text showing the selected `ArenaMode`.
+#[derive(Component)]
+struct ArenaModeText;
+
+/// Start screen text showing the selected `Difficulty`.
+#[derive(Component)]
+struct DifficultyText;
+
 //----------------------------------------------------------------
 
 pub struct MenuPlugin;
@@ -24,6 +36,11 @@ pub struct MenuPlugin;
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
         app.add_system_set(SystemSet::on_enter(AppState::StartMenu).with_system(start_menu))
+            .add_system_set(
+                SystemSet::on_update(AppState::StartMenu)
+                    .with_system(arena_mode_text_system)
+                    .with_system(difficulty_text_system),
+            )
             .add_system_set(SystemSet::on_enter(AppGameState::Pause).with_system(pause_menu))
             .add_system_set(SystemSet::on_enter(AppGameState::GameOver).with_system(gameover_menu))
             .add_system(menu_input_system)
@@ -47,6 +64,10 @@ fn setup(mut commands: Commands) {
     input_map.insert(GamepadButtonType::Start, MenuAction::PauseUnpause);
     input_map.insert(GamepadButtonType::South, MenuAction::Accept);
     input_map.insert(GamepadButtonType::East, MenuAction::Quit);
+    input_map.insert(KeyCode::M, MenuAction::NextArenaMode);
+    input_map.insert(GamepadButtonType::West, MenuAction::NextArenaMode);
+    input_map.insert(KeyCode::D, MenuAction::NextDifficulty);
+    input_map.insert(GamepadButtonType::North, MenuAction::NextDifficulty);
 
     // Insert MenuAction resources
     commands.insert_resource(input_map);
@@ -55,7 +76,10 @@ fn setup(mut commands: Commands) {
 
 //----------------------------------------------------------------
 
-fn start_menu(mut commands: Commands, assets: ResMut<UiAssets>) {
+fn start_menu(
+    mut commands: Commands, assets: ResMut<UiAssets>, mode: Res<ArenaMode>,
+    difficulty: Res<Difficulty>,
+) {
     commands
         .spawn((
             NodeBundle {
@@ -72,7 +96,7 @@ fn start_menu(mut commands: Commands, assets: ResMut<UiAssets>) {
         ))
         .with_children(|parent| {
             parent.spawn((TextBundle {
-                style: Style { ..default() },
+                style: default(),
                 text: Text::from_section(
                     "SpaceGuardian",
                     TextStyle {
@@ -86,7 +110,7 @@ fn start_menu(mut commands: Commands, assets: ResMut<UiAssets>) {
 
             parent.spawn((
                 TextBundle {
-                    style: Style { ..default() },
+                    style: default(),
                     text: Text::from_section(
                         "enter",
                         TextStyle {
@@ -99,10 +123,68 @@ fn start_menu(mut commands: Commands, assets: ResMut<UiAssets>) {
                 },
                 DrawBlinkTimer(Timer::from_seconds(0.5f32, TimerMode::Repeating)),
             ));
+
+            parent.spawn((
+                TextBundle {
+                    style: Style { margin: UiRect::top(Val::Px(30f32)), ..default() },
+                    text: Text::from_section(
+                        arena_mode_label(*mode),
+                        TextStyle {
+                            font: assets.font.clone(),
+                            font_size: 25f32,
+                            color: Color::rgb_u8(0x00, 0x88, 0x88),
+                        },
+                    ),
+                    ..default()
+                },
+                ArenaModeText,
+            ));
+
+            parent.spawn((
+                TextBundle {
+                    style: default(),
+                    text: Text::from_section(
+                        difficulty_label(*difficulty),
+                        TextStyle {
+                            font: assets.font.clone(),
+                            font_size: 25f32,
+                            color: Color::rgb_u8(0x00, 0x88, 0x88),
+                        },
+                    ),
+                    ..default()
+                },
+                DifficultyText,
+            ));
         });
 }
 
-fn gameover_menu(mut commands: Commands, assets: ResMut<UiAssets>) {
+fn arena_mode_label(mode: ArenaMode) -> String {
+    format!("arena: {} (m)", mode.name())
+}
+
+fn arena_mode_text_system(mode: Res<ArenaMode>, mut query: Query<&mut Text, With<ArenaModeText>>) {
+    if mode.is_changed() {
+        for mut text in &mut query {
+            text.sections[0].value = arena_mode_label(*mode);
+        }
+    }
+}
+
+fn difficulty_label(difficulty: Difficulty) -> String {
+    format!("difficulty: {} (d)", difficulty.name())
+}
+
+fn difficulty_text_system(
+    difficulty: Res<Difficulty>, mut query: Query<&mut Text, With<DifficultyText>>,
+) {
+    if difficulty.is_changed() {
+        for mut text in &mut query {
+            text.sections[0].value = difficulty_label(*difficulty);
+        }
+    }
+}
+
+fn gameover_menu(mut commands: Commands, assets: ResMut<UiAssets>, difficulty: Res<Difficulty>) {
     commands
         .spawn((
             NodeBundle {
@@ -119,7 +201,7 @@ fn gameover_menu(mut commands: Commands, assets: ResMut<UiAssets>) {
         ))
         .with_children(|parent| {
             parent.spawn((TextBundle {
-                style: Style { ..default() },
+                style: default(),
                 text: Text::from_section(
                     "Game Over",
                     TextStyle {
@@ -131,9 +213,22 @@ fn gameover_menu(mut commands: Commands, assets: ResMut<UiAssets>) {
                 ..default()
             },));
 
+            parent.spawn((TextBundle {
+                style: default(),
+                text: Text::from_section(
+                    difficulty.name(),
+                    TextStyle {
+                        font: assets.font.clone(),
+                        font_size: 30f32,
+                        color: Color::rgb_u8(0x88, 0x22, 0x22),
+                    },
+                ),
+                ..default()
+            },));
+
             parent.spawn((
                 TextBundle {
-                    style: Style { ..default() },
+                    style: default(),
                     text: Text::from_section(
                         "enter",
                         TextStyle {
@@ -167,7 +262,7 @@ fn pause_menu(mut commands: Commands, assets: ResMut<UiAssets>) {
         .with_children(|parent| {
             parent.spawn((
                 TextBundle {
-                    style: Style { ..default() },
+                    style: default(),
                     text: Text::from_section(
                         "pause",
                         TextStyle {
@@ -186,7 +281,7 @@ fn pause_menu(mut commands: Commands, assets: ResMut<UiAssets>) {
 //----------------------------------------------------------------
 
 fn menu_blink_system(time: Res<Time>, mut query: Query<(&mut DrawBlinkTimer, &mut Visibility)>) {
-    for (mut blink_timer, mut visibility) in query.iter_mut() {
+    for (mut blink_timer, mut visibility) in &mut query {
         blink_timer.0.tick(time.delta());
 
         if blink_timer.0.finished() {
@@ -207,9 +302,10 @@ fn menu_blink_system(time: Res<Time>, mut query: Query<(&mut DrawBlinkTimer, &mu
 fn menu_input_system(
     mut state: ResMut<State<AppState>>, mut gamestate: ResMut<State<AppGameState>>,
     menu_action_state: Res<ActionState<MenuAction>>, mut rapier_cfg: ResMut<RapierConfiguration>,
+    mut arena_mode: ResMut<ArenaMode>, mut difficulty: ResMut<Difficulty>,
     mut app_exit_events: EventWriter<AppExit>,
 ) {
-    let want_menu = state.current() != &AppState::StartMenu
+    let want_menu = state.current() == &AppState::Game
         && menu_action_state.just_pressed(MenuAction::ExitToMenu);
     if want_menu {
         state.set(AppState::StartMenu).unwrap();
@@ -218,7 +314,18 @@ fn menu_input_system(
     }
 
     match *state.current() {
+        AppState::Loading => {
+            if menu_action_state.just_pressed(MenuAction::Quit) {
+                app_exit_events.send(AppExit);
+            }
+        }
         AppState::StartMenu => {
+            if menu_action_state.just_pressed(MenuAction::NextArenaMode) {
+                *arena_mode = arena_mode.next();
+            }
+            if menu_action_state.just_pressed(MenuAction::NextDifficulty) {
+                *difficulty = difficulty.next();
+            }
             if menu_action_state.just_pressed(MenuAction::Accept) {
                 state.set(AppState::Game).unwrap();
                 gamestate.set(AppGameState::Game).unwrap();
@@ -332,6 +439,8 @@ mod keys {
             MenuAction::PauseUnpause => (KeyCode::Escape, GamepadButtonType::Start),
             MenuAction::ExitToMenu => (KeyCode::Back, GamepadButtonType::Select),
             MenuAction::Quit => (KeyCode::Escape, GamepadButtonType::East),
+            MenuAction::NextArenaMode => (KeyCode::M, GamepadButtonType::West),
+            MenuAction::NextDifficulty => (KeyCode::D, GamepadButtonType::North),
         }
     }
 }
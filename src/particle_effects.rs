@@ -73,7 +73,7 @@ fn update_thrust_particles(
 ) {
     for (impulse, children) in impulse.iter() {
         if impulse.impulse.length() != 0f32 {
-            for &child in children.iter() {
+            for &child in children {
                 if let Ok(mut effect) = exhaust_effect.get_mut(child) {
                     if let Some(spawner) = effect.maybe_spawner() {
                         spawner.reset();
@@ -17,6 +17,11 @@ pub enum PlayerAction {
     SlowDown,
 }
 
+/// When present, the ship gets no `InputMap` and its `ActionState` is driven
+/// from code, e.g. by a replay playback or a headless test.
+#[derive(Debug, Default, Resource)]
+pub struct ScriptedShipInput;
+
 pub struct ShipAsteroidContactEvent {
     pub ship: Entity,
     pub asteroid: Entity,
@@ -31,13 +36,23 @@ impl Plugin for PlayerShipPlugin {
         app.add_plugin(InputManagerPlugin::<PlayerAction>::default());
         app.add_event::<ShipAsteroidContactEvent>()
             .add_system_set(SystemSet::on_enter(AppState::Game).with_system(spawn_ship))
-            .add_system_set(
-                SystemSet::on_update(AppState::Game)
-                    .with_system(ship_input_system)
-                    .with_system(ship_dampening_system)
+            .add_system_set(SystemSet::on_update(AppState::Game).with_system(ship_invincible_color))
+            .add_fixed_system_set(
+                SimulationStage::Gameplay,
+                SystemSet::new()
+                    .with_run_criteria(run_unpaused)
                     .with_system(ship_timers_system)
-                    .with_system(ship_invincible_color)
-                    .with_system(ship_damage.after(ContactLabel)),
+                    .with_system(
+                        ship_input_system.label(StepOrder::Ships).after(ship_timers_system),
+                    )
+                    .with_system(ship_dampening_system.after(ship_input_system)),
+            )
+            .add_fixed_system_set(
+                SimulationStage::PostPhysics,
+                SystemSet::new()
+                    .with_run_criteria(run_in_game)
+                    // Volatile blasts send contacts of their own.
+                    .with_system(ship_damage.after(ContactLabel).after(StepOrder::Asteroids)),
             );
     }
 }
@@ -48,7 +63,10 @@ impl Plugin for PlayerShipPlugin {
 #[derive(Component)]
 pub struct ExhaustEffect;
 
-fn spawn_ship(mut commands: Commands, handles: Res<SpriteAssets>) {
+fn spawn_ship(
+    mut commands: Commands, config: Res<GameConfig>, handles: Res<SpriteAssets>,
+    manifest: Res<AssetManifest>, scripted: Option<Res<ScriptedShipInput>>,
+) {
     let mut input_map = InputMap::new([
         (KeyCode::W, PlayerAction::Forward),
         (KeyCode::Up, PlayerAction::Forward),
@@ -88,50 +106,59 @@ fn spawn_ship(mut commands: Commands, handles: Res<SpriteAssets>) {
         PlayerAction::RotateLeft,
     );
 
-    let mut invincible_timer = Timer::from_seconds(INVINCIBLE_TIME, TimerMode::Once);
+    let mut invincible_timer = Timer::from_seconds(config.ship.invincible_time, TimerMode::Once);
     // Immediately consume the timer, we don't want invincibility at creation.
-    invincible_timer.tick(Duration::from_secs_f32(INVINCIBLE_TIME));
+    invincible_timer.tick(Duration::from_secs_f32(config.ship.invincible_time));
 
-    commands.spawn((
+    let sprite: &SpriteEntry = manifest.sprite("player_ship");
+    let mut ship = commands.spawn((
         SpriteBundle {
-            sprite: Sprite { custom_size: Some(Vec2::new(30f32, 20f32)), ..default() },
-            transform: Transform { translation: Vec3::new(0f32, 0f32, 1f32), ..default() },
+            sprite: Sprite { custom_size: sprite.custom_size(), ..default() },
+            transform: Transform {
+                translation: Vec3::new(0f32, 0f32, 1f32),
+                scale: sprite.transform_scale(),
+                ..default()
+            },
             texture: handles.player_ship.clone(),
             ..default()
         },
         Ship {
-            rotation_speed: 3f32,
-            thrust: 60f32,
-            life: START_LIFE,
-            cannon_timer: Timer::from_seconds(0.2f32, TimerMode::Once),
+            rotation_speed: config.ship.rotation_speed,
+            thrust: config.ship.thrust,
+            life: config.ship.start_life,
+            cannon_timer: Timer::from_seconds(config.ship.cannon_cooldown, TimerMode::Once),
             player_id: 1u32,
             invincible_timer,
             invincible_time_secs: 0f32,
         },
         ForState { states: vec![AppState::Game] },
         RigidBody::Dynamic,
-        Collider::ball(13.5f32),
+        Collider::ball(sprite.radius()),
+        ColliderFromSprite,
+        Wrapping,
         ExternalImpulse::default(),
         Velocity::linear(Vec2::ZERO),
         ActiveEvents::COLLISION_EVENTS,
-        InputManagerBundle::<PlayerAction> { action_state: ActionState::default(), input_map },
+        ActionState::<PlayerAction>::default(),
     ));
+    if scripted.is_none() {
+        ship.insert(input_map);
+    }
 }
 
 //----------------------------------------------------------------
 
-fn ship_dampening_system(time: Res<Time>, mut query: Query<&mut Velocity, With<Ship>>) {
-    for mut velocity in query.iter_mut() {
-        let elapsed: f32 = time.delta_seconds();
-        velocity.angvel *= 0.1f32.powf(elapsed);
-        velocity.linvel *= 0.4f32.powf(elapsed);
+fn ship_dampening_system(mut query: Query<&mut Velocity, With<Ship>>) {
+    for mut velocity in &mut query {
+        velocity.angvel *= 0.1f32.powf(TIME_STEP);
+        velocity.linvel *= 0.4f32.powf(TIME_STEP);
     }
 }
 
-fn ship_timers_system(time: Res<Time>, mut ship: Query<&mut Ship>) {
-    for mut ship in ship.iter_mut() {
-        ship.cannon_timer.tick(time.delta());
-        ship.invincible_timer.tick(time.delta());
+fn ship_timers_system(mut ship: Query<&mut Ship>) {
+    for mut ship in &mut ship {
+        ship.cannon_timer.tick(fixed_delta());
+        ship.invincible_timer.tick(fixed_delta());
     }
 }
 
@@ -145,10 +172,9 @@ fn ship_input_system(
         &Transform,
         &mut Ship,
     )>,
-    time: Res<Time>,
 ) {
     if gamestate.current() == &AppGameState::Game {
-        for (action_state, mut impulse, mut velocity, transform, mut ship) in query.iter_mut() {
+        for (action_state, mut impulse, mut velocity, transform, mut ship) in &mut query {
             let thrust: f32 = if action_state.pressed(PlayerAction::Forward) { 1f32 } else { 0f32 };
             let brake = if action_state.pressed(PlayerAction::Backward) { 0.05f32 } else { 1f32 };
 
@@ -208,7 +234,7 @@ fn ship_input_system(
 }
 
 fn ship_damage(
-    mut commands: Commands, mut gamestate: ResMut<State<AppGameState>>,
+    mut commands: Commands, config: Res<GameConfig>, mut gamestate: ResMut<State<AppGameState>>,
     mut ship_asteroid_contact_events: EventReader<ShipAsteroidContactEvent>,
     mut explosion_spawn_events: EventWriter<SpawnExplosionEvent>,
     mut ships: Query<(&mut Ship, &Transform)>,
@@ -241,7 +267,7 @@ fn ship_damage(
             }
             ship.invincible_timer.reset();
         } else if ship.invincible_time_secs + ship.invincible_timer.elapsed_secs()
-            < MAX_INVINCIBLE_TIME
+            < config.ship.max_invincible_time
         {
             // Contact while invincible, re-arm the invincibility time if
             // allowed.
@@ -252,7 +278,7 @@ fn ship_damage(
 }
 
 fn ship_invincible_color(mut ships: Query<(&Ship, &mut Sprite)>) {
-    for (ship, mut ship_sprite) in ships.iter_mut() {
+    for (ship, mut ship_sprite) in &mut ships {
         if ship.invincible_timer.finished() {
             ship_sprite.color = Color::WHITE;
         } else {
@@ -0,0 +1,597 @@
+use std::path::{
+    Path,
+    PathBuf,
+};
+
+use bevy::app::AppExit;
+
+use crate::prelude::*;
+
+/// First bytes of a replay file.
+const REPLAY_MAGIC: &[u8; 4] = b"SSRP";
+/// Bumped whenever the layout of a replay file changes.
+const REPLAY_VERSION: u8 = 3u8;
+
+/// How a recorded game ended.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ReplayOutcome {
+    /// `SimulationTick` at which the ship was destroyed.
+    pub tick: u64,
+    /// `Arena::score` at that tick.
+    pub score: u32,
+}
+
+/// Settings a game was started with, applied again on playback.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplaySettings {
+    pub difficulty: Difficulty,
+    pub arena_mode: ArenaMode,
+    /// `ArenaBounds` size, kept for the whole game.
+    pub arena_size: Vec2,
+    /// Whether the `Director` spawned instead of the `Waves`.
+    pub director: bool,
+    /// `BaseConfig` the `difficulty` applies to, not reloaded while the game
+    /// is recorded or played back.
+    pub config: GameConfig,
+    /// Outline radius in `px` of each of `AsteroidSize::ALL`, from the
+    /// manifest and skin.
+    pub asteroid_radii: [f32; 3],
+    /// Whether the colliders were built from the sprites, which headless
+    /// games have none of, see `ColliderFromSprite`.
+    pub sprite_colliders: bool,
+}
+
+impl Default for ReplaySettings {
+    fn default() -> Self {
+        Self {
+            difficulty: Difficulty::default(),
+            arena_mode: ArenaMode::default(),
+            arena_size: Vec2::new(ARENA_WIDTH, ARENA_HEIGHT),
+            director: false,
+            config: GameConfig::default(),
+            asteroid_radii: asteroid_radii(&AssetManifest::default()),
+            sprite_colliders: false,
+        }
+    }
+}
+
+/// Outline radius of each of `AsteroidSize::ALL` in `manifest`.
+fn asteroid_radii(manifest: &AssetManifest) -> [f32; 3] {
+    AsteroidSize::ALL.map(|size| {
+        let sprite: &SpriteEntry = manifest.sprite(size.sprite_name());
+        sprite.radius() * sprite.scale()
+    })
+}
+
+/// Seed, settings and inputs of one game, enough to play it again step by
+/// step.
+///
+/// The `PlayerAction`s pressed during a tick are packed in a bitmask, and
+/// consecutive identical masks are stored once with their repeat count.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Replay {
+    pub seed: u64,
+    pub settings: ReplaySettings,
+    /// `(ticks, actions)` runs, in order.
+    runs: Vec<(u32, u8)>,
+    /// `None` when the game was left before a game over.
+    pub outcome: Option<ReplayOutcome>,
+}
+
+impl Replay {
+    pub const fn new(seed: u64, settings: ReplaySettings) -> Self {
+        Self { seed, settings, runs: Vec::new(), outcome: None }
+    }
+
+    /// Bitmask of the pressed actions.
+    pub fn actions_mask(action_state: &ActionState<PlayerAction>) -> u8 {
+        PlayerAction::variants()
+            .filter(|action| action_state.pressed(*action))
+            .fold(0u8, |mask, action| mask | (1u8 << action.index()))
+    }
+
+    /// Append the actions of the next tick.
+    pub fn push(&mut self, actions: u8) {
+        match self.runs.last_mut() {
+            Some((ticks, last)) if *last == actions && *ticks < u32::MAX => *ticks += 1u32,
+            _ => self.runs.push((1u32, actions)),
+        }
+    }
+
+    /// Number of recorded ticks.
+    pub fn len(&self) -> u64 {
+        self.runs.iter().map(|(ticks, _)| u64::from(*ticks)).sum()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    /// Actions of every tick, in order.
+    pub fn ticks(&self) -> impl Iterator<Item = u8> + '_ {
+        self.runs.iter().flat_map(|(ticks, actions)| std::iter::repeat_n(*actions, *ticks as usize))
+    }
+
+    /// # Panics
+    ///
+    /// Never, every `GameConfig` serializes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let settings: &ReplaySettings = &self.settings;
+        let config: String =
+            ron::to_string(&settings.config).expect("a config should serialize to RON");
+        let mut bytes: Vec<u8> =
+            Vec::with_capacity(58usize + config.len() + self.runs.len() * 5usize);
+        bytes.extend_from_slice(REPLAY_MAGIC);
+        bytes.push(REPLAY_VERSION);
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.push(difficulty_to_byte(settings.difficulty));
+        bytes.push(match settings.arena_mode {
+            ArenaMode::Wraparound => 0u8,
+            ArenaMode::Bounded => 1u8,
+        });
+        bytes.extend_from_slice(&settings.arena_size.x.to_le_bytes());
+        bytes.extend_from_slice(&settings.arena_size.y.to_le_bytes());
+        bytes.push(u8::from(settings.director));
+        bytes.push(u8::from(settings.sprite_colliders));
+        for radius in settings.asteroid_radii {
+            bytes.extend_from_slice(&radius.to_le_bytes());
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        bytes.extend_from_slice(&(config.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(config.as_bytes());
+        match self.outcome {
+            Some(outcome) => {
+                bytes.push(1u8);
+                bytes.extend_from_slice(&outcome.tick.to_le_bytes());
+                bytes.extend_from_slice(&outcome.score.to_le_bytes());
+            }
+            None => bytes.push(0u8),
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        bytes.extend_from_slice(&(self.runs.len() as u32).to_le_bytes());
+        for (ticks, actions) in &self.runs {
+            bytes.extend_from_slice(&ticks.to_le_bytes());
+            bytes.push(*actions);
+        }
+        bytes
+    }
+
+    /// # Errors
+    ///
+    /// If `bytes` isn't a replay of the current version.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = ByteReader(bytes);
+        if reader.take::<4>()? != *REPLAY_MAGIC {
+            return Err("not a replay file".to_string());
+        }
+        let [version] = reader.take::<1>()?;
+        if version != REPLAY_VERSION {
+            return Err(format!("unsupported replay version {version}"));
+        }
+        let seed: u64 = u64::from_le_bytes(reader.take()?);
+        let [difficulty] = reader.take::<1>()?;
+        let difficulty: Difficulty = difficulty_from_byte(difficulty)?;
+        let arena_mode: ArenaMode = match reader.take::<1>()? {
+            [0u8] => ArenaMode::Wraparound,
+            [1u8] => ArenaMode::Bounded,
+            [mode] => return Err(format!("invalid arena mode {mode}")),
+        };
+        let arena_size =
+            Vec2::new(f32::from_le_bytes(reader.take()?), f32::from_le_bytes(reader.take()?));
+        if !(arena_size.cmpgt(Vec2::ZERO).all() && arena_size.is_finite()) {
+            return Err(format!("invalid arena size {arena_size}"));
+        }
+        let director: bool = match reader.take::<1>()? {
+            [0u8] => false,
+            [1u8] => true,
+            [flag] => return Err(format!("invalid director flag {flag}")),
+        };
+        let sprite_colliders: bool = match reader.take::<1>()? {
+            [0u8] => false,
+            [1u8] => true,
+            [flag] => return Err(format!("invalid sprite colliders flag {flag}")),
+        };
+        let mut asteroid_radii = [0f32; 3];
+        for radius in &mut asteroid_radii {
+            *radius = f32::from_le_bytes(reader.take()?);
+            if *radius <= 0f32 || !radius.is_finite() {
+                return Err(format!("invalid asteroid radius {radius}"));
+            }
+        }
+        let config_len: u32 = u32::from_le_bytes(reader.take()?);
+        let config: GameConfig = std::str::from_utf8(reader.take_slice(config_len as usize)?)
+            .map_err(|err| err.to_string())
+            .and_then(GameConfig::parse)
+            .map_err(|err| format!("invalid config: {err}"))?;
+        let settings = ReplaySettings {
+            difficulty,
+            arena_mode,
+            arena_size,
+            director,
+            config,
+            asteroid_radii,
+            sprite_colliders,
+        };
+        let outcome: Option<ReplayOutcome> = match reader.take::<1>()? {
+            [0u8] => None,
+            [1u8] => Some(ReplayOutcome {
+                tick: u64::from_le_bytes(reader.take()?),
+                score: u32::from_le_bytes(reader.take()?),
+            }),
+            [flag] => return Err(format!("invalid outcome flag {flag}")),
+        };
+        let n_runs: u32 = u32::from_le_bytes(reader.take()?);
+        let runs = (0..n_runs)
+            .map(|_| Ok((u32::from_le_bytes(reader.take()?), reader.take::<1>()?[0])))
+            .collect::<Result<Vec<(u32, u8)>, String>>()?;
+        if !reader.0.is_empty() {
+            return Err("trailing bytes after the last tick".to_string());
+        }
+        Ok(Self { seed, settings, runs, outcome })
+    }
+
+    /// Refuse to play back with other colliders than the recording had.
+    ///
+    /// # Errors
+    ///
+    /// If `sprite_colliders` differs from `ReplaySettings::sprite_colliders`.
+    pub fn check_colliders(&self, sprite_colliders: bool) -> Result<(), String> {
+        if self.settings.sprite_colliders == sprite_colliders {
+            return Ok(());
+        }
+        let recorded: &str =
+            if self.settings.sprite_colliders { "in a window" } else { "headless" };
+        Err(format!("replay recorded {recorded} must be played back the same way"))
+    }
+
+    /// # Errors
+    ///
+    /// If the file can't be read, or `from_bytes` fails.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let bytes: Vec<u8> = std::fs::read(path)
+            .map_err(|err| format!("could not read `{}`: {err}", path.display()))?;
+        Self::from_bytes(&bytes).map_err(|err| format!("`{}`: {err}", path.display()))
+    }
+
+    /// # Errors
+    ///
+    /// If the file can't be written.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        std::fs::write(path, self.to_bytes())
+            .map_err(|err| format!("could not write `{}`: {err}", path.display()))
+    }
+}
+
+const fn difficulty_to_byte(difficulty: Difficulty) -> u8 {
+    match difficulty {
+        Difficulty::Easy => 0u8,
+        Difficulty::Normal => 1u8,
+        Difficulty::Hard => 2u8,
+        Difficulty::Insane => 3u8,
+    }
+}
+
+fn difficulty_from_byte(byte: u8) -> Result<Difficulty, String> {
+    match byte {
+        0u8 => Ok(Difficulty::Easy),
+        1u8 => Ok(Difficulty::Normal),
+        2u8 => Ok(Difficulty::Hard),
+        3u8 => Ok(Difficulty::Insane),
+        _ => Err(format!("invalid difficulty {byte}")),
+    }
+}
+
+/// Cursor over the bytes of a replay file.
+struct ByteReader<'a>(&'a [u8]);
+
+impl<'a> ByteReader<'a> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        Ok(self.take_slice(N)?.try_into().expect("head should be N bytes long"))
+    }
+
+    fn take_slice(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.0.len() < len {
+            return Err("truncated replay file".to_string());
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Ok(head)
+    }
+}
+
+//----------------------------------------------------------------
+
+/// Records every game into `path`, overwriting the previous one.
+#[derive(Debug, Resource)]
+pub struct ReplayRecorder {
+    pub path: PathBuf,
+    replay: Replay,
+    saved: bool,
+}
+
+/// Feeds the actions of `replay` to the ship instead of its `InputMap`.
+#[derive(Debug, Resource)]
+pub struct ReplayPlayback {
+    pub replay: Replay,
+    actions: Vec<u8>,
+    outcome: Option<ReplayOutcome>,
+}
+
+//----------------------------------------------------------------
+
+/// Record the games to `record`, or play `playback` back.
+///
+/// The `RngPlugin` must be seeded with the seed of `playback`, and the game
+/// started with its `ReplaySettings`, see `GameAppBuilder::playback`.
+pub struct ReplayPlugin {
+    pub record: Option<PathBuf>,
+    pub playback: Option<Replay>,
+}
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        if let Some(replay) = &self.playback {
+            info!("Playing back {} ticks", replay.len());
+            app.insert_resource(ReplayPlayback {
+                replay: replay.clone(),
+                actions: replay.ticks().collect(),
+                outcome: None,
+            })
+            .insert_resource(ScriptedShipInput)
+            .add_startup_system(apply_asteroid_radii)
+            .add_system_set(SystemSet::on_enter(AppState::Game).with_system(reset_playback))
+            .add_fixed_system_set(
+                SimulationStage::PreStep,
+                SystemSet::new()
+                    .with_run_criteria(run_in_game)
+                    .with_system(playback_actions.after(SimulationTickLabel)),
+            );
+        } else if let Some(path) = &self.record {
+            info!("Recording games to `{}`", path.display());
+            app.insert_resource(ReplayRecorder {
+                path: path.clone(),
+                replay: Replay::default(),
+                saved: true,
+            })
+            .add_system_set(SystemSet::on_enter(AppState::Game).with_system(start_recording))
+            .add_system_set(SystemSet::on_exit(AppState::Game).with_system(save_recording))
+            .add_system_to_stage(CoreStage::Last, save_recording_on_exit)
+            .add_fixed_system_set(
+                SimulationStage::PreStep,
+                SystemSet::new()
+                    .with_run_criteria(run_in_game)
+                    .with_system(record_actions.after(SimulationTickLabel)),
+            );
+        }
+        app.add_fixed_system_set(
+            SimulationStage::Spawn,
+            SystemSet::new().with_run_criteria(run_in_game).with_system(replay_outcome),
+        );
+    }
+}
+
+//----------------------------------------------------------------
+
+fn start_recording(
+    mut recorder: ResMut<ReplayRecorder>, game_rng: Res<GameRng>, difficulty: Res<Difficulty>,
+    arena_mode: Res<ArenaMode>, bounds: Res<ArenaBounds>, director: Option<Res<Director>>,
+    base: Res<BaseConfig>, manifest: Res<AssetManifest>, images: Option<Res<Assets<Image>>>,
+) {
+    let settings = ReplaySettings {
+        difficulty: *difficulty,
+        arena_mode: *arena_mode,
+        arena_size: bounds.size,
+        director: director.is_some(),
+        config: base.0.clone(),
+        asteroid_radii: asteroid_radii(&manifest),
+        sprite_colliders: images.is_some(),
+    };
+    recorder.replay = Replay::new(game_rng.seed(), settings);
+    recorder.saved = false;
+}
+
+fn record_actions(
+    gamestate: Res<State<AppGameState>>, mut recorder: ResMut<ReplayRecorder>,
+    ships: Query<&ActionState<PlayerAction>, With<Ship>>,
+) {
+    if gamestate.current() == &AppGameState::Game {
+        // Keep ticks aligned even once the ship is gone.
+        let actions: u8 = ships.get_single().map_or(0u8, Replay::actions_mask);
+        recorder.replay.push(actions);
+    }
+}
+
+fn save_recording(mut recorder: ResMut<ReplayRecorder>) {
+    if recorder.saved {
+        return;
+    }
+    recorder.saved = true;
+    match recorder.replay.save(&recorder.path) {
+        Ok(()) => info!("Saved {} ticks to `{}`", recorder.replay.len(), recorder.path.display()),
+        Err(err) => error!("Replay not saved: {err}"),
+    }
+}
+
+fn save_recording_on_exit(recorder: ResMut<ReplayRecorder>, mut exit_events: EventReader<AppExit>) {
+    if exit_events.iter().next().is_some() {
+        save_recording(recorder);
+    }
+}
+
+/// Size the asteroids as in the recording, whatever the manifest and skin.
+fn apply_asteroid_radii(playback: Res<ReplayPlayback>, mut manifest: ResMut<AssetManifest>) {
+    for (size, radius) in AsteroidSize::ALL.into_iter().zip(playback.replay.settings.asteroid_radii)
+    {
+        if let Some(sprite) = manifest.sprites.get_mut(size.sprite_name()) {
+            sprite.radius = Some(radius);
+            sprite.scale = None;
+        }
+    }
+}
+
+fn reset_playback(mut playback: ResMut<ReplayPlayback>) {
+    playback.outcome = None;
+}
+
+fn playback_actions(
+    gamestate: Res<State<AppGameState>>, tick: Res<SimulationTick>, playback: Res<ReplayPlayback>,
+    mut ships: Query<&mut ActionState<PlayerAction>, With<Ship>>,
+) {
+    if gamestate.current() != &AppGameState::Game {
+        return; // early exit.
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    let actions: u8 = tick
+        .0
+        .checked_sub(1u64)
+        .and_then(|i| playback.actions.get(i as usize))
+        .copied()
+        .unwrap_or(0u8);
+    for mut action_state in &mut ships {
+        for action in PlayerAction::variants() {
+            if actions & (1u8 << action.index()) == 0u8 {
+                action_state.release(action);
+            } else {
+                action_state.press(action);
+            }
+        }
+    }
+}
+
+/// Note how the game ended, as soon as the ship is destroyed.
+fn replay_outcome(
+    tick: Res<SimulationTick>, arena: Res<Arena>, ships: Query<(), With<Ship>>,
+    recorder: Option<ResMut<ReplayRecorder>>, playback: Option<ResMut<ReplayPlayback>>,
+) {
+    if !ships.is_empty() || tick.0 == 0u64 {
+        return; // early exit.
+    }
+    let outcome = ReplayOutcome { tick: tick.0, score: arena.score };
+
+    if let Some(mut recorder) = recorder {
+        if recorder.replay.outcome.is_none() {
+            recorder.replay.outcome = Some(outcome);
+            save_recording(recorder);
+        }
+    }
+    if let Some(mut playback) = playback {
+        if playback.outcome.is_some() {
+            return;
+        }
+        playback.outcome = Some(outcome);
+        match playback.replay.outcome {
+            Some(expected) if expected == outcome => {
+                info!("Replay reproduced: score {} at tick {}", outcome.score, outcome.tick);
+            }
+            Some(expected) => warn!(
+                "Replay diverged: score {} at tick {}, recorded score {} at tick {}",
+                outcome.score, outcome.tick, expected.score, expected.tick
+            ),
+            None => warn!("Replay diverged: game over at tick {}, none recorded", outcome.tick),
+        }
+    }
+}
+
+//----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_utils::*;
+
+    #[test]
+    fn test_push_merges_runs() {
+        let mut replay = Replay::new(1u64, ReplaySettings::default());
+        for actions in [0u8, 0u8, 3u8, 3u8, 3u8, 0u8] {
+            replay.push(actions);
+        }
+        assert_eq!(replay.runs, vec![(2u32, 0u8), (3u32, 3u8), (1u32, 0u8)]);
+        assert_eq!(replay.len(), 6u64);
+        assert_eq!(replay.ticks().collect::<Vec<u8>>(), vec![0u8, 0u8, 3u8, 3u8, 3u8, 0u8]);
+    }
+
+    #[test]
+    fn test_bytes_roundtrip() {
+        let settings = ReplaySettings {
+            difficulty: Difficulty::Insane,
+            arena_mode: ArenaMode::Bounded,
+            arena_size: Vec2::new(1024f32, 768f32),
+            director: true,
+            config: GameConfig::parse("(ship: (thrust: 90.0), laser: (speed: 500.0))").unwrap(),
+            asteroid_radii: [60f32, 30f32, 12.5f32],
+            sprite_colliders: true,
+        };
+        let mut replay = Replay::new(0xDEAD_BEEF_u64, settings);
+        for actions in [1u8, 1u8, 16u8, 0u8] {
+            replay.push(actions);
+        }
+        replay.outcome = Some(ReplayOutcome { tick: 4u64, score: 120u32 });
+        assert!(replay.check_colliders(true).is_ok());
+        assert!(replay.check_colliders(false).is_err());
+        assert_eq!(Replay::from_bytes(&replay.to_bytes()), Ok(replay));
+    }
+
+    #[test]
+    fn test_truncated_bytes_rejected() {
+        let mut replay = Replay::new(9u64, ReplaySettings::default());
+        replay.push(2u8);
+        let bytes: Vec<u8> = replay.to_bytes();
+        assert!(Replay::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(Replay::from_bytes(b"nope").is_err());
+    }
+
+    /// Lose the only life to an asteroid dropped on the ship, after spinning
+    /// and firing at the first wave. Only the recording presses the actions.
+    fn scripted_game(mut app: App, record: bool) -> App {
+        if let Some(ship) = ship(&mut app) {
+            ship.life = 1u32;
+        }
+        for tick in 1..600u64 {
+            if record {
+                let turn = if tick % 120 < 60 {
+                    PlayerAction::RotateLeft
+                } else {
+                    PlayerAction::RotateRight
+                };
+                press(&mut app, &[PlayerAction::Fire, turn]);
+            }
+            if tick == 400u64 {
+                let (size, material) = (AsteroidSize::Big, AsteroidMaterial::Rock);
+                spawn_asteroid(&mut app, size, material, Vec2::ZERO, Vec2::ZERO);
+            }
+            run_ticks(&mut app, 1u64);
+        }
+        app
+    }
+
+    #[test]
+    fn test_playback_reproduces_outcome() {
+        let path: PathBuf = std::env::temp_dir().join("spaceshooter-test-playback.replay");
+        let config = GameConfig::parse("(ship: (thrust: 90.0, cannon_cooldown: 0.3))").unwrap();
+        let builder = GameAppBuilder::new()
+            .headless(true)
+            .start_in_game(true)
+            .config(config.clone())
+            .difficulty(Difficulty::Hard)
+            .arena_mode(ArenaMode::Bounded)
+            .window_size(Some(Vec2::new(1024f32, 768f32)))
+            .seed(Some(14u64));
+        let mut app = builder.record(Some(path)).build();
+        run_ticks(&mut app, 1u64);
+        let app = scripted_game(app, true);
+        let replay: Replay = app.world.resource::<ReplayRecorder>().replay.clone();
+        assert_eq!(replay.settings.difficulty, Difficulty::Hard);
+        assert_eq!(replay.settings.arena_size, Vec2::new(1024f32, 768f32));
+        assert_eq!(replay.settings.config.ship, config.ship);
+        let recorded: ReplayOutcome = replay.outcome.expect("the ship should have been destroyed");
+        assert!(recorded.score > 0u32);
+
+        let builder = GameAppBuilder::new().headless(true).start_in_game(true);
+        let mut app = builder.playback(Some(replay)).build();
+        run_ticks(&mut app, 1u64);
+        assert_eq!(*app.world.resource::<ArenaMode>(), ArenaMode::Bounded);
+        assert_eq!(app.world.resource::<BaseConfig>().0.ship, config.ship);
+        let app = scripted_game(app, false);
+        assert_eq!(app.world.resource::<ReplayPlayback>().outcome, Some(recorded));
+    }
+}
@@ -0,0 +1,105 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::prelude::*;
+
+/// Seeded random number generators, one stream per subsystem so that cosmetic
+/// randomness can't perturb gameplay.
+///
+/// All streams are derived from a single `seed` and rewound each time a game
+/// starts, so two runs with the same seed and inputs play out the same way.
+#[derive(Debug, Resource)]
+pub struct GameRng {
+    seed: u64,
+    /// Positions and velocities of newly spawned asteroids and guardians.
+    pub spawning: ChaCha8Rng,
+    /// Fragments of destroyed asteroids and guardians.
+    pub splitting: ChaCha8Rng,
+    /// Outlines of newly spawned asteroids.
+    pub shapes: ChaCha8Rng,
+    /// Purely visual randomness.
+    pub effects: ChaCha8Rng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            spawning: Self::stream(seed, 0u64),
+            splitting: Self::stream(seed, 1u64),
+            effects: Self::stream(seed, 2u64),
+            shapes: Self::stream(seed, 3u64),
+        }
+    }
+
+    pub const fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Rewind every stream to the start of the seed.
+    pub fn reset(&mut self) {
+        *self = Self::new(self.seed);
+    }
+
+    fn stream(seed: u64, stream: u64) -> ChaCha8Rng {
+        let mut rng: ChaCha8Rng = ChaCha8Rng::seed_from_u64(seed);
+        rng.set_stream(stream);
+        rng
+    }
+}
+
+//----------------------------------------------------------------
+
+/// Insert the `GameRng`, seeded with `seed` or with a random seed when `None`.
+pub struct RngPlugin {
+    pub seed: Option<u64>,
+}
+
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        let seed: u64 = self.seed.unwrap_or_else(|| rand::thread_rng().gen());
+        info!("Game seed: {seed}");
+
+        app.insert_resource(GameRng::new(seed))
+            .add_system_set(SystemSet::on_enter(AppState::Game).with_system(reset_rng));
+    }
+}
+
+//----------------------------------------------------------------
+
+fn reset_rng(mut rng: ResMut<GameRng>) {
+    rng.reset();
+}
+
+//----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let (mut a, mut b) = (GameRng::new(42u64), GameRng::new(42u64));
+        for _ in 0..16 {
+            assert_eq!(a.spawning.gen::<u32>(), b.spawning.gen::<u32>());
+        }
+    }
+
+    #[test]
+    fn test_streams_are_independent() {
+        let (mut a, mut b) = (GameRng::new(7u64), GameRng::new(7u64));
+        for _ in 0..16 {
+            a.effects.gen::<u32>();
+        }
+        assert_eq!(a.spawning.gen::<u32>(), b.spawning.gen::<u32>());
+        assert_eq!(a.splitting.gen::<u32>(), b.splitting.gen::<u32>());
+    }
+
+    #[test]
+    fn test_reset_rewinds() {
+        let mut rng = GameRng::new(3u64);
+        let first: u32 = rng.splitting.gen();
+        rng.reset();
+        assert_eq!(first, rng.splitting.gen::<u32>());
+    }
+}
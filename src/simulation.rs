@@ -0,0 +1,297 @@
+use bevy::{
+    ecs::schedule::ShouldRun,
+    time::FixedTimesteps,
+    transform::TransformSystem,
+};
+
+use crate::prelude::*;
+
+/// Label of the `FixedTimestep` driving the `FixedUpdateStage`.
+pub const FIXED_TIMESTEP_LABEL: &str = "simulation";
+
+/// Top level stage running the whole gameplay simulation, Rapier included, at
+/// a fixed rate of one step every `TIME_STEP` seconds.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, StageLabel)]
+pub struct FixedUpdateStage;
+
+/// Stages of one simulation step, nested in the `FixedUpdateStage`.
+///
+/// Rapier's `PhysicsStages` run between `Gameplay` and `PostPhysics`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, StageLabel)]
+pub enum SimulationStage {
+    /// Bookkeeping before anything moves.
+    PreStep,
+    /// Inputs, timers and spawners.
+    Gameplay,
+    /// Contacts reported by the physics step and their consequences.
+    PostPhysics,
+    /// Entities requested during the step, ready for the next one.
+    Spawn,
+}
+
+/// Number of simulation steps played since the game started, pauses
+/// excluded.
+#[derive(Debug, Default, Clone, Copy, Resource)]
+pub struct SimulationTick(pub u64);
+
+/// Systems running after `SimulationTick` is advanced for the current step.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
+pub struct SimulationTickLabel;
+
+/// Order of the systems of a `SimulationStage` which share data, so that a
+/// step always plays out the same way whatever the parallel executor picks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemLabel)]
+pub enum StepOrder {
+    /// Input of the ships, sending the `LaserSpawnEvent`s.
+    Ships,
+    /// Wraparound of the bodies which left the arena.
+    Movement,
+    Asteroids,
+    /// After `Asteroids`, both draw from the `GameRng`.
+    Guardians,
+}
+
+/// Interpolate the rendered `GlobalTransform` between the two last simulation
+/// steps. Added automatically to every `RigidBody`.
+#[derive(Debug, Default, Component)]
+pub struct RenderInterpolation {
+    previous: Option<(Vec3, Quat)>,
+}
+
+//----------------------------------------------------------------
+
+/// Helpers to add systems to the nested `SimulationStage`s.
+pub trait FixedUpdateAppExt {
+    fn add_fixed_system_set(&mut self, stage: SimulationStage, system_set: SystemSet) -> &mut Self;
+}
+
+impl FixedUpdateAppExt for App {
+    fn add_fixed_system_set(&mut self, stage: SimulationStage, system_set: SystemSet) -> &mut Self {
+        self.stage(FixedUpdateStage, |schedule: &mut Schedule| {
+            schedule.add_system_set_to_stage(stage, system_set)
+        })
+    }
+}
+
+/// Run criteria of fixed systems, matching
+/// `SystemSet::on_update(AppState::Game)` whose state driver only lives in
+/// `CoreStage::Update`.
+pub fn run_in_game(state: Res<State<AppState>>) -> ShouldRun {
+    if state.current() == &AppState::Game {
+        ShouldRun::Yes
+    } else {
+        ShouldRun::No
+    }
+}
+
+/// Like `run_in_game`, but also stop while the game is paused so that
+/// nothing moves behind the pause menu.
+pub fn run_unpaused(state: Res<State<AppState>>, gamestate: Res<State<AppGameState>>) -> ShouldRun {
+    if state.current() == &AppState::Game && gamestate.current() != &AppGameState::Pause {
+        ShouldRun::Yes
+    } else {
+        ShouldRun::No
+    }
+}
+
+/// Duration of one simulation step, to tick timers inside the
+/// `FixedUpdateStage`.
+pub fn fixed_delta() -> Duration {
+    Duration::from_secs_f32(TIME_STEP)
+}
+
+//----------------------------------------------------------------
+
+/// Adds Rapier and steps it in lock-step with the gameplay systems.
+#[derive(Debug, Default)]
+pub struct SimulationPlugin {
+    /// Run exactly one step per `App::update` instead of one every
+    /// `TIME_STEP` seconds of real time, for headless runs.
+    pub step_per_update: bool,
+}
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(RapierConfiguration {
+            timestep_mode: TimestepMode::Fixed { dt: TIME_STEP, substeps: 1usize },
+            ..default()
+        })
+        .add_plugin(
+            RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(10f32)
+                .with_default_system_setup(false),
+        );
+
+        let mut schedule = Schedule::default();
+        schedule
+            .add_stage(
+                SimulationStage::PreStep,
+                SystemStage::parallel()
+                    .with_system(insert_render_interpolation)
+                    .with_system(store_previous_transforms)
+                    .with_system(advance_tick.label(SimulationTickLabel)),
+            )
+            .add_stage_after(
+                SimulationStage::PreStep,
+                SimulationStage::Gameplay,
+                SystemStage::parallel(),
+            )
+            .add_stage_after(
+                SimulationStage::Gameplay,
+                PhysicsStages::SyncBackend,
+                SystemStage::parallel().with_system_set(
+                    RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsStages::SyncBackend),
+                ),
+            )
+            .add_stage_after(
+                PhysicsStages::SyncBackend,
+                PhysicsStages::StepSimulation,
+                SystemStage::parallel().with_system_set(
+                    RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsStages::StepSimulation),
+                ),
+            )
+            .add_stage_after(
+                PhysicsStages::StepSimulation,
+                PhysicsStages::Writeback,
+                SystemStage::parallel().with_system_set(
+                    RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsStages::Writeback),
+                ),
+            )
+            .add_stage_after(
+                PhysicsStages::Writeback,
+                SimulationStage::PostPhysics,
+                SystemStage::parallel(),
+            )
+            .add_stage_after(
+                SimulationStage::PostPhysics,
+                SimulationStage::Spawn,
+                SystemStage::parallel(),
+            );
+
+        let schedule = if self.step_per_update {
+            schedule
+        } else {
+            schedule.with_run_criteria(
+                FixedTimestep::step(f64::from(TIME_STEP)).with_label(FIXED_TIMESTEP_LABEL),
+            )
+        };
+
+        app.init_resource::<SimulationTick>()
+            .add_system_set(SystemSet::on_enter(AppState::Game).with_system(reset_tick))
+            .add_stage_after(CoreStage::Update, FixedUpdateStage, schedule)
+            // Also catch despawns happening outside of the simulation.
+            .add_stage_before(
+                CoreStage::Last,
+                PhysicsStages::DetectDespawn,
+                SystemStage::parallel().with_system_set(
+                    RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsStages::DetectDespawn),
+                ),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                interpolate_transforms.after(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+//----------------------------------------------------------------
+
+fn reset_tick(mut tick: ResMut<SimulationTick>) {
+    *tick = SimulationTick::default();
+}
+
+fn advance_tick(
+    state: Res<State<AppState>>, gamestate: Res<State<AppGameState>>,
+    mut tick: ResMut<SimulationTick>,
+) {
+    if state.current() == &AppState::Game && gamestate.current() == &AppGameState::Game {
+        tick.0 += 1u64;
+    }
+}
+
+fn insert_render_interpolation(
+    mut commands: Commands, query: Query<Entity, (With<RigidBody>, Without<RenderInterpolation>)>,
+) {
+    for entity in &query {
+        commands.entity(entity).insert(RenderInterpolation::default());
+    }
+}
+
+/// Remember where bodies are before stepping, and undo the interpolated
+/// `GlobalTransform` so that Rapier doesn't mistake it for a teleport.
+fn store_previous_transforms(
+    mut query: Query<(
+        &Transform,
+        &mut GlobalTransform,
+        &mut RenderInterpolation,
+        Option<&Children>,
+    )>,
+    mut children_query: Query<
+        (&Transform, &mut GlobalTransform, Option<&Children>),
+        Without<RenderInterpolation>,
+    >,
+) {
+    for (transform, mut global_transform, mut interpolation, children) in &mut query {
+        interpolation.previous = Some((transform.translation, transform.rotation));
+        *global_transform = GlobalTransform::from(*transform);
+        if let Some(children) = children {
+            propagate_to_children(&global_transform, children, &mut children_query);
+        }
+    }
+}
+
+fn interpolate_transforms(
+    fixed_timesteps: Res<FixedTimesteps>, bounds: Res<ArenaBounds>,
+    mut query: Query<(&Transform, &mut GlobalTransform, &RenderInterpolation, Option<&Children>)>,
+    mut children_query: Query<
+        (&Transform, &mut GlobalTransform, Option<&Children>),
+        Without<RenderInterpolation>,
+    >,
+) {
+    #[allow(clippy::cast_possible_truncation)]
+    let alpha: f32 = fixed_timesteps
+        .get(FIXED_TIMESTEP_LABEL)
+        .map_or(1f32, |state| state.overstep_percentage().min(1f64) as f32);
+
+    for (transform, mut global_transform, interpolation, children) in &mut query {
+        let Some((translation, rotation)) = interpolation.previous else { continue };
+
+        // Don't smear entities wrapping around the arena across the screen.
+        if translation.truncate().distance(transform.translation.truncate())
+            > bounds.size.min_element() / 2f32
+        {
+            continue;
+        }
+        *global_transform = GlobalTransform::from(Transform {
+            translation: translation.lerp(transform.translation, alpha),
+            rotation: rotation.slerp(transform.rotation, alpha),
+            scale: transform.scale,
+        });
+        // The children were propagated from the stepped transform, follow the
+        // interpolated one instead.
+        if let Some(children) = children {
+            propagate_to_children(&global_transform, children, &mut children_query);
+        }
+    }
+}
+
+/// Recompute the `GlobalTransform` of the descendants of a body whose own one
+/// was rewritten after `TransformPropagate`.
+fn propagate_to_children(
+    parent: &GlobalTransform, children: &[Entity],
+    query: &mut Query<
+        (&Transform, &mut GlobalTransform, Option<&Children>),
+        Without<RenderInterpolation>,
+    >,
+) {
+    for &child in children {
+        let Ok((transform, mut global_transform, grandchildren)) = query.get_mut(child) else {
+            continue;
+        };
+        *global_transform = parent.mul_transform(*transform);
+        let global_transform: GlobalTransform = *global_transform;
+        if let Some(grandchildren) = grandchildren {
+            let grandchildren: Vec<Entity> = grandchildren.to_vec();
+            propagate_to_children(&global_transform, &grandchildren, query);
+        }
+    }
+}
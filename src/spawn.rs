@@ -0,0 +1,371 @@
+use serde::Deserialize;
+
+use crate::prelude::*;
+
+/// Side of the marker shown where something is about to spawn, in `px`.
+const WARNING_SIZE: f32 = 36f32;
+/// Seconds between two blinks of a warning marker.
+const WARNING_BLINK: f32 = 0.15f32;
+/// Entry points tried before settling for the one farthest from the ships.
+const ENTRY_TRIES: usize = 8usize;
+
+/// Where an entity of a `SpawnOrder` enters the arena.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Deserialize, Default)]
+pub enum SpawnEdge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+    /// Any of the four edges, picked at random for each member.
+    #[default]
+    Any,
+    /// A portal opening inside the arena.
+    Portal,
+}
+
+impl SpawnEdge {
+    /// Random point along this edge of an arena of `half_size`, or inside it
+    /// for a `Portal`.
+    pub fn point(self, rng: &mut impl Rng, half_size: Vec2) -> Vec2 {
+        let (half_width, half_height): (f32, f32) = (half_size.x, half_size.y);
+        let edge: Self = match self {
+            Self::Any => [Self::Top, Self::Bottom, Self::Left, Self::Right][rng.gen_range(0..4)],
+            edge => edge,
+        };
+        match edge {
+            Self::Top => Vec2::new(rng.gen_range(half_width.neg()..half_width), half_height),
+            Self::Bottom => {
+                Vec2::new(rng.gen_range(half_width.neg()..half_width), half_height.neg())
+            }
+            Self::Left => {
+                Vec2::new(half_width.neg(), rng.gen_range(half_height.neg()..half_height))
+            }
+            Self::Portal => {
+                let inner: Vec2 = half_size * 0.75f32;
+                Vec2::new(
+                    rng.gen_range(inner.x.neg()..inner.x),
+                    rng.gen_range(inner.y.neg()..inner.y),
+                )
+            }
+            Self::Right | Self::Any => {
+                Vec2::new(half_width, rng.gen_range(half_height.neg()..half_height))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum SpawnKind {
+    Asteroid(AsteroidSize, AsteroidMaterial),
+    Guardian(GuardianSize),
+}
+
+/// Bring an asteroid or a guardian into the arena, heading for its middle.
+///
+/// The entry point is kept out of the `safe_radius` of every `Ship` when
+/// possible, and a warning marker blinks there for `spawn_warning` seconds
+/// before the entity spawns through `AsteroidSpawnEvent` or
+/// `GuardianSpawnEvent`.
+#[derive(Debug, Clone, Copy)]
+pub struct SpawnOrder {
+    pub kind: SpawnKind,
+    pub edge: SpawnEdge,
+    /// Linear speed in `px/s`.
+    pub speed: f32,
+    /// Angular velocity in `rad/s`.
+    pub angvel: f32,
+}
+
+/// Marker at the entry point of a pending `SpawnOrder`.
+#[derive(Debug, Component)]
+pub struct SpawnWarning {
+    pub kind: SpawnKind,
+    velocity: Vec2,
+    angvel: f32,
+    timer: Timer,
+}
+
+//----------------------------------------------------------------
+
+pub struct SpawnPlugin;
+
+impl Plugin for SpawnPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SpawnOrder>()
+            .add_fixed_system_set(
+                SimulationStage::Gameplay,
+                SystemSet::new()
+                    .with_run_criteria(run_in_game)
+                    .with_system(timed_spawns.before(StepOrder::Asteroids)),
+            )
+            .add_fixed_system_set(
+                SimulationStage::Spawn,
+                SystemSet::new()
+                    .with_run_criteria(run_unpaused)
+                    .with_system(spawn_warnings.before(StepOrder::Asteroids))
+                    .with_system(spawn_orders.after(spawn_warnings).before(StepOrder::Asteroids)),
+            );
+    }
+}
+
+//----------------------------------------------------------------
+
+/// Random point of `edge` farthest from the `ships`, the first one out of
+/// their `safe_radius`. Distances are measured across the edges when the
+/// arena `wraps`.
+pub fn entry_point(
+    rng: &mut impl Rng, edge: SpawnEdge, half_size: Vec2, ships: &[Vec2], safe_radius: f32,
+    wraps: bool,
+) -> Vec2 {
+    let distance = |a: Vec2, b: Vec2| {
+        let delta: Vec2 = (a - b).abs();
+        if wraps {
+            delta.min(half_size * 2f32 - delta).length()
+        } else {
+            delta.length()
+        }
+    };
+    let mut best: (f32, Vec2) = (f32::NEG_INFINITY, Vec2::ZERO);
+    for _ in 0..ENTRY_TRIES {
+        let point: Vec2 = edge.point(rng, half_size);
+        let clearance: f32 =
+            ships.iter().map(|ship| distance(point, *ship)).fold(f32::INFINITY, f32::min);
+        if clearance >= safe_radius {
+            return point;
+        }
+        if clearance > best.0 {
+            best = (clearance, point);
+        }
+    }
+    best.1
+}
+
+/// Classic ever-accelerating spawn timers, only used when no `Waves` are
+/// loaded, nor a `Director`.
+#[allow(clippy::too_many_arguments)]
+fn timed_spawns(
+    gamestate: Res<State<AppGameState>>, config: Res<GameConfig>, bounds: Res<ArenaBounds>,
+    waves: Res<Waves>, director: Option<Res<Director>>, mut arena: ResMut<Arena>,
+    mut game_rng: ResMut<GameRng>, mut spawn_orders: EventWriter<SpawnOrder>,
+    asteroids: Query<(), With<Asteroid>>, guardians: Query<(), With<Guardian>>,
+    warnings: Query<&SpawnWarning>,
+) {
+    if gamestate.current() != &AppGameState::Game || !waves.is_empty() || director.is_some() {
+        return; // early exit.
+    }
+    let (pending_asteroids, pending_guardians): (usize, usize) = pending_spawns(&warnings);
+    let rng = &mut game_rng.spawning;
+    let max_speed: f32 = bounds.size.min_element() / 4f32;
+
+    let n_asteroid: usize = asteroids.iter().count() + pending_asteroids;
+    if spawn_timer_due(&mut arena.asteroid_spawn_timer, n_asteroid, config.arena.max_asteroid_count)
+    {
+        spawn_orders.send(SpawnOrder {
+            kind: SpawnKind::Asteroid(AsteroidSize::Big, AsteroidMaterial::random(rng)),
+            edge: random_edge(rng, &config),
+            speed: rng.gen_range(0f32..max_speed) * config.arena.asteroid_speed_scale,
+            angvel: rng.gen_range(10f32.neg()..10f32),
+        });
+    }
+
+    let n_guardian: usize = guardians.iter().count() + pending_guardians;
+    if spawn_timer_due(&mut arena.guardian_spawn_timer, n_guardian, config.arena.max_guardian_count)
+    {
+        spawn_orders.send(SpawnOrder {
+            kind: SpawnKind::Guardian(GuardianSize::Medium),
+            edge: random_edge(rng, &config),
+            speed: rng.gen_range(0f32..max_speed),
+            angvel: rng.gen_range(2f32.neg()..2f32),
+        });
+    }
+}
+
+/// Whether `timer` is due with at most `max_count` entities in the arena, in
+/// which case it's rearmed 20% shorter.
+fn spawn_timer_due(timer: &mut Timer, count: usize, max_count: usize) -> bool {
+    if !timer.tick(fixed_delta()).finished() {
+        return false;
+    }
+    timer.reset();
+    if count > max_count {
+        return false;
+    }
+    let duration: f32 = (0.8f32 * timer.duration().as_secs_f32()).max(0.1f32);
+    timer.set_duration(Duration::from_secs_f32(duration));
+    true
+}
+
+/// Asteroids and guardians behind a `SpawnWarning`, yet to spawn.
+pub fn pending_spawns(warnings: &Query<&SpawnWarning>) -> (usize, usize) {
+    let asteroids: usize =
+        warnings.iter().filter(|warning| matches!(warning.kind, SpawnKind::Asteroid(..))).count();
+    (asteroids, warnings.iter().count() - asteroids)
+}
+
+/// Any edge, or a portal for a `portal_share` of the spawns.
+pub fn random_edge(rng: &mut impl Rng, config: &GameConfig) -> SpawnEdge {
+    if rng.gen::<f32>() < config.arena.portal_share {
+        SpawnEdge::Portal
+    } else {
+        SpawnEdge::Any
+    }
+}
+
+/// Place the ordered spawns behind their warning marker.
+#[allow(clippy::too_many_arguments)]
+fn spawn_orders(
+    mut commands: Commands, config: Res<GameConfig>, bounds: Res<ArenaBounds>,
+    mode: Res<ArenaMode>, mut game_rng: ResMut<GameRng>, mut orders: EventReader<SpawnOrder>,
+    ships: Query<&Transform, With<Ship>>,
+    mut asteroid_spawn_events: EventWriter<AsteroidSpawnEvent>,
+    mut guardian_spawn_events: EventWriter<GuardianSpawnEvent>,
+) {
+    let half_size: Vec2 = bounds.half_size();
+    let ships: Vec<Vec2> = ships.iter().map(|transform| transform.translation.truncate()).collect();
+    let wraps: bool = *mode == ArenaMode::Wraparound;
+    let rng = &mut game_rng.spawning;
+
+    for order in orders.iter() {
+        let position: Vec2 =
+            entry_point(rng, order.edge, half_size, &ships, config.arena.safe_radius, wraps);
+        let target = Vec2::new(
+            rng.gen_range((half_size.x.neg() / 2f32)..(half_size.x / 2f32)),
+            rng.gen_range((half_size.y.neg() / 2f32)..(half_size.y / 2f32)),
+        );
+        let velocity: Vec2 = (target - position).normalize_or_zero() * order.speed;
+
+        if config.arena.spawn_warning <= 0f32 {
+            send_spawn(
+                order.kind,
+                position,
+                velocity,
+                order.angvel,
+                &mut asteroid_spawn_events,
+                &mut guardian_spawn_events,
+            );
+            continue;
+        }
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgba_u8(0xDD, 0x44, 0x22, 0xAA),
+                    custom_size: Some(Vec2::splat(WARNING_SIZE)),
+                    ..default()
+                },
+                transform: Transform::from_translation(position.extend(2f32))
+                    .with_rotation(Quat::from_rotation_z(PI / 4f32)),
+                ..default()
+            },
+            SpawnWarning {
+                kind: order.kind,
+                velocity,
+                angvel: order.angvel,
+                timer: Timer::from_seconds(config.arena.spawn_warning, TimerMode::Once),
+            },
+            DrawBlinkTimer(Timer::from_seconds(WARNING_BLINK, TimerMode::Repeating)),
+            ForState { states: vec![AppState::Game] },
+        ));
+    }
+}
+
+/// Spawn what the markers warned about once their time is up.
+fn spawn_warnings(
+    mut commands: Commands, mut warnings: Query<(Entity, &Transform, &mut SpawnWarning)>,
+    mut asteroid_spawn_events: EventWriter<AsteroidSpawnEvent>,
+    mut guardian_spawn_events: EventWriter<GuardianSpawnEvent>,
+) {
+    for (entity, transform, mut warning) in &mut warnings {
+        if !warning.timer.tick(fixed_delta()).finished() {
+            continue;
+        }
+        send_spawn(
+            warning.kind,
+            transform.translation.truncate(),
+            warning.velocity,
+            warning.angvel,
+            &mut asteroid_spawn_events,
+            &mut guardian_spawn_events,
+        );
+        commands.entity(entity).despawn();
+    }
+}
+
+fn send_spawn(
+    kind: SpawnKind, position: Vec2, velocity: Vec2, angvel: f32,
+    asteroid_spawn_events: &mut EventWriter<AsteroidSpawnEvent>,
+    guardian_spawn_events: &mut EventWriter<GuardianSpawnEvent>,
+) {
+    match kind {
+        SpawnKind::Asteroid(size, material) => asteroid_spawn_events.send(AsteroidSpawnEvent {
+            size,
+            material,
+            x: position.x,
+            y: position.y,
+            vx: velocity.x,
+            vy: velocity.y,
+            angvel,
+            mass: None,
+            shape: None,
+        }),
+        SpawnKind::Guardian(size) => guardian_spawn_events.send(GuardianSpawnEvent {
+            size,
+            x: position.x,
+            y: position.y,
+            vx: velocity.x,
+            vy: velocity.y,
+            angvel,
+            mass: None,
+        }),
+    }
+}
+
+//----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+    use crate::app::test_utils::*;
+
+    #[test]
+    fn test_entry_point_keeps_out_of_safe_radius() {
+        let mut rng = ChaCha8Rng::seed_from_u64(1u64);
+        let half_size = Vec2::new(640f32, 400f32);
+        let ships = [Vec2::new(0f32, 380f32)];
+        for _ in 0..100 {
+            let point = entry_point(&mut rng, SpawnEdge::Top, half_size, &ships, 200f32, false);
+            assert!(point.distance(ships[0]) >= 200f32);
+        }
+        // Across the bottom edge, the ship is close to the top one.
+        let ships = [Vec2::new(0f32, -390f32)];
+        for _ in 0..100 {
+            let point = entry_point(&mut rng, SpawnEdge::Top, half_size, &ships, 200f32, true);
+            assert!(point.x.abs() >= 190f32);
+        }
+    }
+
+    #[test]
+    fn test_guardian_spawns_at_marker() {
+        let mut app = headless_game(16u64);
+        app.world.send_event(SpawnOrder {
+            kind: SpawnKind::Guardian(GuardianSize::Small),
+            edge: SpawnEdge::Portal,
+            speed: 0f32,
+            angvel: 0f32,
+        });
+        run_ticks(&mut app, 1u64);
+        let mut warnings = app.world.query::<(&Transform, &SpawnWarning)>();
+        let marker: Vec2 = warnings
+            .iter(&app.world)
+            .find(|(_, warning)| matches!(warning.kind, SpawnKind::Guardian(_)))
+            .map(|(transform, _)| transform.translation.truncate())
+            .expect("a marker should announce the guardian");
+
+        run_ticks(&mut app, 62u64);
+        let mut guardians = app.world.query_filtered::<&Transform, With<Guardian>>();
+        let guardian: Vec2 = guardians.single(&app.world).translation.truncate();
+        assert!(guardian.distance(marker) < 5f32, "{guardian} spawned away from {marker}");
+    }
+}
@@ -5,6 +5,8 @@ use crate::prelude::*;
 /// Main state enumerated differentiating `Menu` from `Game` 'scenes'.
 #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
 pub enum AppState {
+    /// Waiting for the `SpriteAssets`, `AudioAssets` and `UiAssets`.
+    Loading,
     StartMenu,
     Game,
 }
@@ -12,20 +14,37 @@ pub enum AppState {
 /// Game state enum, differentiating several phase of the game
 #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
 pub enum AppGameState {
-    /// Invalid used when AppState is NOT Game
+    /// Invalid used when `AppState` is NOT Game
     Invalid,
     Game,
     Pause,
     GameOver,
 }
 
+/// State entered once every asset is loaded.
+#[derive(Debug, Clone, Copy, Resource)]
+pub struct StateAfterLoading(pub AppState);
+
 //----------------------------------------------------------------
 
-pub struct StatesPlugin;
+/// Add the `AppState` and `AppGameState`, starting with `AppState::Loading`
+/// then going to the start menu or directly in a game.
+#[derive(Debug, Default)]
+pub struct StatesPlugin {
+    pub start_in_game: bool,
+}
 
 impl Plugin for StatesPlugin {
     fn build(&self, app: &mut App) {
-        for state in [AppState::StartMenu, AppState::Game] {
+        app.add_state(AppState::Loading).add_state(AppGameState::Invalid).insert_resource(
+            StateAfterLoading(if self.start_in_game {
+                AppState::Game
+            } else {
+                AppState::StartMenu
+            }),
+        );
+
+        for state in [AppState::Loading, AppState::StartMenu, AppState::Game] {
             app.add_system_set(
                 SystemSet::on_enter(state).with_system(state_enter_despawn::<AppState>),
             );
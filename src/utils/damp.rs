@@ -3,6 +3,7 @@ use crate::prelude::*;
 //----------------------------------------------------------------
 
 pub trait Damp {
+    #[must_use]
     fn damp(self, target: Self, speed: f32, delta_seconds: f32) -> Self;
 }
 
@@ -0,0 +1,107 @@
+use crate::prelude::*;
+
+//----------------------------------------------------------------
+
+/// Body about to break apart, see `Fragmentation::fragments`.
+#[derive(Debug, Clone, Copy)]
+pub struct Fragmentation {
+    /// Mass of the body, shared evenly by its fragments.
+    pub mass: f32,
+    pub velocity: Velocity,
+    /// Velocity the hit adds to the body, away from the impact point.
+    pub push: Vec2,
+    /// Kinetic energy driving the fragments apart, per unit of `mass`, in
+    /// `px²/s²`.
+    pub energy: f32,
+    /// Distance in `px` of the fragments from the center of the body.
+    pub spacing: f32,
+}
+
+/// Piece of a broken body, placed relative to its center.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fragment {
+    pub offset: Vec2,
+    pub linvel: Vec2,
+    pub mass: f32,
+}
+
+impl Fragmentation {
+    /// Direction of the first fragment, the one of `push` turned by `angle`.
+    pub fn heading(&self, angle: f32) -> f32 {
+        self.push.y.atan2(self.push.x) + angle
+    }
+
+    /// Split the body into `count` fragments of even mass, evenly spread
+    /// around its center, the first one towards `push` turned by `angle`.
+    ///
+    /// Each fragment moves as its point of the body did, spin included, plus
+    /// the `push`, so the momentum of the body and of the hit is kept. The
+    /// fragments are then kicked apart along their offsets, with kicks that
+    /// cancel out and sum up to the `energy`. A single fragment only gets the
+    /// `push`.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn fragments(&self, count: usize, angle: f32) -> Vec<Fragment> {
+        let mass: f32 = self.mass / count.max(1usize) as f32;
+        if count == 1usize {
+            return vec![Fragment {
+                offset: Vec2::ZERO,
+                linvel: self.velocity.linvel + self.push,
+                mass,
+            }];
+        }
+
+        let kick: f32 = (2f32 * self.energy.max(0f32)).sqrt();
+        let heading: f32 = self.heading(angle);
+        (0..count)
+            .map(|i| {
+                let direction: Vec2 =
+                    Vec2::from_angle(heading + 2f32 * PI * i as f32 / count as f32);
+                let offset: Vec2 = direction * self.spacing;
+                let spin: Vec2 = offset.perp() * self.velocity.angvel;
+                Fragment {
+                    offset,
+                    linvel: self.velocity.linvel + self.push + spin + direction * kick,
+                    mass,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Push of a hit at `impact` on a body centered on `center`, see
+/// `Fragmentation::push`.
+pub fn impact_push(center: Vec2, impact: Vec2) -> Vec2 {
+    (center - impact).normalize_or_zero() * FRAGMENT_PUSH
+}
+
+//----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fragments_keep_momentum_and_energy() {
+        let fragmentation = Fragmentation {
+            mass: 6f32,
+            velocity: Velocity { linvel: Vec2::new(30f32, -10f32), angvel: 0f32 },
+            push: Vec2::new(0f32, 20f32),
+            energy: 800f32,
+            spacing: 10f32,
+        };
+        let fragments: Vec<Fragment> = fragmentation.fragments(3usize, 0.3f32);
+        let mass: f32 = fragments.iter().map(|fragment| fragment.mass).sum();
+        let momentum: Vec2 = fragments.iter().map(|fragment| fragment.linvel * fragment.mass).sum();
+        let drift: Vec2 = fragmentation.velocity.linvel + fragmentation.push;
+        let energy: f32 = fragments
+            .iter()
+            .map(|fragment| 0.5f32 * fragment.mass * (fragment.linvel - drift).length_squared())
+            .sum();
+
+        assert!((mass - 6f32).abs() < 1e-4f32);
+        assert!(momentum.abs_diff_eq(drift * 6f32, 1e-3f32));
+        assert!((energy - 4800f32).abs() < 1e-1f32);
+        // The first fragment flies on with the push.
+        assert!(fragments[0].linvel.y > drift.y);
+    }
+}
@@ -5,6 +5,7 @@ use crate::prelude::*;
 //----------------------------------------------------------------
 
 pub trait Interpolation {
+    #[must_use]
     fn lerp(self, end: Self, factor: f32) -> Self;
 }
 
@@ -1,18 +1,28 @@
 // [REFERENCE](https://github.com/cryscan/summer-jam/blob/master/src/utils/mod.rs)
 
-mod collide;
 mod damp;
+mod fragment;
 mod interpolation;
+mod outline;
+mod polygon;
 
 pub use self::{
-    collide::*,
     damp::*,
+    fragment::*,
     interpolation::*,
+    outline::*,
+    polygon::*,
 };
 pub use super::*;
 
 //----------------------------------------------------------------
 
+/// Path of a file inside the `assets` folder, resolved the same way the
+/// `AssetServer` does. Used for data files read outside of the asset pipeline.
+pub fn asset_path(path: &str) -> std::path::PathBuf {
+    bevy::asset::FileAssetIo::get_base_path().join("assets").join(path)
+}
+
 pub fn cleanup_system<T>(mut commands: Commands, query: Query<Entity, With<T>>)
 where
     T: Component,
@@ -22,6 +32,9 @@ where
     }
 }
 
+/// # Panics
+///
+/// If a transition to another state is already queued.
 pub fn escape_system(mut app_state: ResMut<State<AppState>>, mut input: ResMut<Input<KeyCode>>) {
     if input.just_pressed(KeyCode::Escape) {
         input.reset(KeyCode::Escape);
@@ -0,0 +1,72 @@
+use bevy::render::render_resource::TextureFormat;
+
+use crate::prelude::*;
+
+//----------------------------------------------------------------
+
+/// Pixels at or below this alpha are outside of the sprite silhouette.
+const ALPHA_THRESHOLD: u8 = 32u8;
+
+/// Outline of the opaque pixels of `image`, in pixels from its center with
+/// `y` up: the outer corners of the first and last opaque pixel of each row.
+pub fn alpha_outline(image: &Image) -> Option<Vec<Vec2>> {
+    if !matches!(
+        image.texture_descriptor.format,
+        TextureFormat::Rgba8UnormSrgb | TextureFormat::Rgba8Unorm
+    ) {
+        return None;
+    }
+    let size = image.texture_descriptor.size;
+    let (width, height): (usize, usize) = (size.width as usize, size.height as usize);
+    let half_size: Vec2 = image.size() / 2f32;
+
+    let mut points: Vec<Vec2> = Vec::new();
+    for (y, row) in image.data.chunks_exact(width * 4usize).take(height).enumerate() {
+        let opaque = |x: &usize| row[x * 4usize + 3usize] > ALPHA_THRESHOLD;
+        let (Some(first), Some(last)) = ((0..width).find(opaque), (0..width).rev().find(opaque))
+        else {
+            continue;
+        };
+        #[allow(clippy::cast_precision_loss)]
+        let (left, right, top): (f32, f32, f32) = (first as f32, (last + 1usize) as f32, y as f32);
+        for x in [left, right] {
+            points.push(Vec2::new(x - half_size.x, half_size.y - top));
+            points.push(Vec2::new(x - half_size.x, half_size.y - top - 1f32));
+        }
+    }
+    (!points.is_empty()).then_some(points)
+}
+
+//----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::render_resource::{
+        Extent3d,
+        TextureDimension,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_alpha_outline_bounds_opaque_pixels() {
+        // 4x4 image with an opaque 2x2 square at its center.
+        let data: Vec<u8> = (0..16)
+            .flat_map(|i| {
+                let (x, y) = (i % 4, i / 4);
+                let alpha = if (1..3).contains(&x) && (1..3).contains(&y) { 255u8 } else { 0u8 };
+                [255u8, 255u8, 255u8, alpha]
+            })
+            .collect();
+        let image = Image::new(
+            Extent3d { width: 4u32, height: 4u32, depth_or_array_layers: 1u32 },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+        );
+        let points = alpha_outline(&image).unwrap();
+        assert!(points.iter().all(|point| point.abs().max_element() <= 1f32));
+        assert!(points.contains(&Vec2::new(-1f32, 1f32)));
+        assert!(points.contains(&Vec2::new(1f32, -1f32)));
+    }
+}
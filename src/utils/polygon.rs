@@ -0,0 +1,189 @@
+use crate::prelude::*;
+
+//----------------------------------------------------------------
+
+/// Simple polygon, its vertices in counterclockwise order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polygon(pub Vec<Vec2>);
+
+impl Polygon {
+    /// Polygon through `points`, turned counterclockwise if needed.
+    pub fn new(mut points: Vec<Vec2>) -> Self {
+        if signed_area(&points) < 0f32 {
+            points.reverse();
+        }
+        Self(points)
+    }
+
+    /// Jagged outline around the origin, of `count` vertices at evenly
+    /// spread angles. Their distance to the origin is `radius` perturbed by
+    /// up to `roughness` of it: smooth value noise around the outline for
+    /// the lumps, plus a bit of jitter for the cracks.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn jagged(rng: &mut impl Rng, radius: f32, count: usize, roughness: f32) -> Self {
+        // Noise values spread around the outline, looping back on the first.
+        let lumps: Vec<f32> = (0..5).map(|_| rng.gen_range(1f32.neg()..1f32)).collect();
+        let points: Vec<Vec2> = (0..count)
+            .map(|i| {
+                let turn: f32 = i as f32 / count as f32;
+                let at: usize = i * lumps.len();
+                let (lump, next): (f32, f32) =
+                    (lumps[at / count], lumps[(at / count + 1usize) % lumps.len()]);
+                // Cosine interpolation, smooth across the noise values.
+                let between: f32 = (at % count) as f32 / count as f32;
+                let blend: f32 = (1f32 - (between * PI).cos()) / 2f32;
+                let noise: f32 = 0.75f32.mul_add(
+                    (next - lump).mul_add(blend, lump),
+                    0.25f32 * rng.gen_range(1f32.neg()..1f32),
+                );
+                Vec2::from_angle(2f32 * PI * turn) * radius * roughness.mul_add(noise, 1f32)
+            })
+            .collect();
+        Self::new(points)
+    }
+
+    pub fn area(&self) -> f32 {
+        signed_area(&self.0)
+    }
+
+    /// Center of mass of the polygon, the mean of its vertices for a
+    /// degenerate one.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn centroid(&self) -> Vec2 {
+        let area: f32 = self.area();
+        if area.abs() <= f32::EPSILON {
+            return self.0.iter().copied().sum::<Vec2>() / self.0.len().max(1usize) as f32;
+        }
+        self.edges().map(|(a, b)| (a + b) * a.perp_dot(b)).sum::<Vec2>() / (6f32 * area)
+    }
+
+    /// Distance from the origin to the farthest vertex.
+    pub fn radius(&self) -> f32 {
+        self.0.iter().map(|point| point.length()).fold(0f32, f32::max)
+    }
+
+    #[must_use]
+    pub fn translated(&self, offset: Vec2) -> Self {
+        Self(self.0.iter().map(|point| *point + offset).collect())
+    }
+
+    #[must_use]
+    pub fn rotated(&self, angle: f32) -> Self {
+        let rotation: Vec2 = Vec2::from_angle(angle);
+        Self(self.0.iter().map(|point| rotation.rotate(*point)).collect())
+    }
+
+    /// Part of the polygon on the side of the line through `point` that
+    /// `normal` points to, `None` if too little is left.
+    pub fn clipped(&self, point: Vec2, normal: Vec2) -> Option<Self> {
+        let inside = |p: Vec2| (p - point).dot(normal) >= 0f32;
+        let mut points: Vec<Vec2> = Vec::new();
+        for (a, b) in self.edges() {
+            if inside(a) {
+                points.push(a);
+            }
+            if inside(a) != inside(b) {
+                let (da, db): (f32, f32) = ((a - point).dot(normal), (b - point).dot(normal));
+                points.push(a.lerp(b, da / (da - db)));
+            }
+        }
+        (points.len() >= 3usize && signed_area(&points) > 1f32).then_some(Self(points))
+    }
+
+    /// Part of the polygon in the wedge from `apex` between the angles
+    /// `start` and `end`, at most half a turn apart.
+    pub fn wedge(&self, apex: Vec2, start: f32, end: f32) -> Option<Self> {
+        self.clipped(apex, Vec2::from_angle(start).perp())?
+            .clipped(apex, Vec2::from_angle(end).perp().neg())
+    }
+
+    /// Triangles covering the polygon, by ear clipping, as indices of its
+    /// vertices in counterclockwise order.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn triangulate(&self) -> Vec<[u32; 3]> {
+        let points: &[Vec2] = &self.0;
+        let mut remaining: Vec<usize> = (0..points.len()).collect();
+        let mut triangles: Vec<[u32; 3]> = Vec::new();
+
+        while remaining.len() > 3usize {
+            let n: usize = remaining.len();
+            let ear: Option<usize> = (0..n).find(|&i| {
+                let (a, b, c) = (
+                    points[remaining[(i + n - 1usize) % n]],
+                    points[remaining[i]],
+                    points[remaining[(i + 1usize) % n]],
+                );
+                (b - a).perp_dot(c - b) > 0f32
+                    && remaining
+                        .iter()
+                        .map(|&j| points[j])
+                        .all(|p| p == a || p == b || p == c || !in_triangle(p, a, b, c))
+            });
+            // Only degenerate outlines have no ear, cut any corner.
+            let i: usize = ear.unwrap_or(0usize);
+            triangles.push([
+                remaining[(i + n - 1usize) % n] as u32,
+                remaining[i] as u32,
+                remaining[(i + 1usize) % n] as u32,
+            ]);
+            remaining.remove(i);
+        }
+        if let [a, b, c] = remaining[..] {
+            triangles.push([a as u32, b as u32, c as u32]);
+        }
+        triangles
+    }
+
+    /// Pairs of consecutive vertices, the last one closing on the first.
+    pub fn edges(&self) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+        self.0.iter().copied().zip(self.0.iter().copied().cycle().skip(1usize))
+    }
+}
+
+fn signed_area(points: &[Vec2]) -> f32 {
+    let next = points.iter().cycle().skip(1usize);
+    points.iter().zip(next).map(|(a, b)| a.perp_dot(*b)).sum::<f32>() / 2f32
+}
+
+fn in_triangle(p: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
+    (b - a).perp_dot(p - a) >= 0f32
+        && (c - b).perp_dot(p - b) >= 0f32
+        && (a - c).perp_dot(p - c) >= 0f32
+}
+
+//----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+
+    #[test]
+    fn test_wedges_cover_the_polygon() {
+        let polygon = Polygon::jagged(&mut ChaCha8Rng::seed_from_u64(5u64), 50f32, 16usize, 0.3f32);
+        let triangles: f32 = polygon
+            .triangulate()
+            .iter()
+            .map(|[a, b, c]| {
+                signed_area(&[
+                    polygon.0[*a as usize],
+                    polygon.0[*b as usize],
+                    polygon.0[*c as usize],
+                ])
+            })
+            .sum();
+        assert!((triangles - polygon.area()).abs() < 1e-2f32);
+
+        let pieces: f32 = [0f32, 1f32, 2f32]
+            .into_iter()
+            .filter_map(|i| {
+                let start: f32 = 2f32 * PI * i / 3f32;
+                polygon.wedge(Vec2::ZERO, start, start + 2f32 * PI / 3f32)
+            })
+            .map(|piece| piece.area())
+            .sum();
+        assert!((pieces - polygon.area()).abs() < 1e-1f32);
+    }
+}
@@ -0,0 +1,390 @@
+use serde::Deserialize;
+
+use crate::prelude::*;
+
+/// Sent whenever a new wave starts, carrying the 1-based wave number.
+pub struct WaveStartedEvent {
+    pub wave: u32,
+}
+
+//----------------------------------------------------------------
+
+/// A batch of identical entities spawned during a wave.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpawnGroup<S> {
+    pub size: S,
+    pub count: u32,
+    /// Asteroids only.
+    #[serde(default)]
+    pub material: AsteroidMaterial,
+    #[serde(default)]
+    pub edge: SpawnEdge,
+    /// Range of linear speed in `px/s`, aimed towards the middle of the arena.
+    pub speed: (f32, f32),
+    /// Range of angular velocity in `rad/s`.
+    #[serde(default)]
+    pub angvel: (f32, f32),
+    /// Seconds after the start of the wave before the first member spawns.
+    #[serde(default)]
+    pub delay: f32,
+    /// Seconds between two members of the group.
+    #[serde(default)]
+    pub interval: f32,
+}
+
+/// One wave of the wave file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WaveDefinition {
+    #[serde(default)]
+    pub asteroids: Vec<SpawnGroup<AsteroidSize>>,
+    #[serde(default)]
+    pub guardians: Vec<SpawnGroup<GuardianSize>>,
+    /// Seconds of calm once the wave is cleared, before the next one starts.
+    #[serde(default = "default_rest")]
+    pub rest: f32,
+}
+
+const fn default_rest() -> f32 {
+    3f32
+}
+
+const fn default_warmup() -> f32 {
+    2f32
+}
+
+const fn default_loop_speed_scale() -> f32 {
+    1f32
+}
+
+/// Root of a wave file such as `assets/waves.ron`.
+#[derive(Debug, Clone, Default, Resource, Deserialize)]
+pub struct Waves {
+    #[serde(rename = "waves")]
+    pub definitions: Vec<WaveDefinition>,
+    /// Seconds before the first wave starts.
+    #[serde(default = "default_warmup")]
+    pub warmup: f32,
+    /// Speed multiplier applied each time the wave list starts over.
+    #[serde(default = "default_loop_speed_scale")]
+    pub loop_speed_scale: f32,
+}
+
+impl Waves {
+    /// Read and validate a wave file.
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be read, or `parse` fails.
+    pub fn load(path: &std::path::Path) -> Result<Self, String> {
+        let source: String = std::fs::read_to_string(path)
+            .map_err(|err| format!("could not read `{}`: {err}", path.display()))?;
+        Self::parse(&source).map_err(|err| format!("`{}`: {err}", path.display()))
+    }
+
+    /// # Errors
+    ///
+    /// If `source` isn't a valid wave file.
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let waves: Self = ron::from_str(source).map_err(|err| err.to_string())?;
+        waves.validate()?;
+        Ok(waves)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if is_negative_or_nan(self.warmup) {
+            return Err(format!("invalid warmup {}", self.warmup));
+        }
+        if is_negative_or_nan(self.loop_speed_scale) {
+            return Err(format!("invalid loop speed scale {}", self.loop_speed_scale));
+        }
+        for (i, wave) in self.definitions.iter().enumerate() {
+            let speeds = wave
+                .asteroids
+                .iter()
+                .map(|group| group.speed)
+                .chain(wave.guardians.iter().map(|group| group.speed));
+            for (min, max) in speeds {
+                if !is_valid_range((min, max)) || min < 0f32 {
+                    return Err(format!("wave {}: invalid speed range ({min}, {max})", i + 1));
+                }
+            }
+            let angvels = wave
+                .asteroids
+                .iter()
+                .map(|group| group.angvel)
+                .chain(wave.guardians.iter().map(|group| group.angvel));
+            for (min, max) in angvels {
+                if !is_valid_range((min, max)) {
+                    return Err(format!("wave {}: invalid angvel range ({min}, {max})", i + 1));
+                }
+            }
+            let timings = wave
+                .asteroids
+                .iter()
+                .map(|group| (group.delay, group.interval))
+                .chain(wave.guardians.iter().map(|group| (group.delay, group.interval)));
+            for (delay, interval) in timings {
+                if is_negative_or_nan(delay) || is_negative_or_nan(interval) {
+                    return Err(format!("wave {}: invalid timing ({delay}, {interval})", i + 1));
+                }
+            }
+            if is_negative_or_nan(wave.rest) {
+                return Err(format!("wave {}: rest must not be negative", i + 1));
+            }
+        }
+        Ok(())
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.definitions.is_empty()
+    }
+}
+
+fn is_negative_or_nan(value: f32) -> bool {
+    value.is_nan() || value < 0f32
+}
+
+/// Finite bounds in order, as `gen_range` needs them.
+fn is_valid_range((min, max): (f32, f32)) -> bool {
+    min.is_finite() && max.is_finite() && min <= max
+}
+
+//----------------------------------------------------------------
+
+/// A wave member waiting for its spawn time.
+#[derive(Debug, Clone, Copy)]
+struct PendingSpawn {
+    at: f32,
+    kind: SpawnKind,
+    edge: SpawnEdge,
+    speed: (f32, f32),
+    angvel: (f32, f32),
+}
+
+/// Progress through the `Waves`, reset each time a game starts.
+#[derive(Debug, Resource)]
+pub struct WaveState {
+    /// Number of the current wave, `0` before the first one started.
+    pub counter: u32,
+    elapsed: f32,
+    /// Pending spawns, sorted so that the next one is last.
+    queue: Vec<PendingSpawn>,
+    rest_timer: Timer,
+}
+
+impl WaveState {
+    fn new(waves: &Waves) -> Self {
+        Self {
+            counter: 0u32,
+            elapsed: 0f32,
+            queue: Vec::new(),
+            rest_timer: Timer::from_seconds(waves.warmup, TimerMode::Once),
+        }
+    }
+
+    /// Queue the next wave, its timings multiplied by `interval_scale`.
+    #[allow(clippy::cast_possible_truncation)]
+    fn start_next(&mut self, waves: &Waves, interval_scale: f32) {
+        let index: usize = self.counter as usize % waves.definitions.len();
+        let cycle: u32 = self.counter / waves.definitions.len() as u32;
+        let scale: f32 = waves.loop_speed_scale.powi(i32::try_from(cycle).unwrap_or(i32::MAX));
+        let wave: &WaveDefinition = &waves.definitions[index];
+
+        self.counter += 1u32;
+        self.elapsed = 0f32;
+        self.rest_timer = Timer::from_seconds(wave.rest * interval_scale, TimerMode::Once);
+        self.queue.clear();
+
+        for group in &wave.asteroids {
+            let kind = SpawnKind::Asteroid(group.size, group.material);
+            self.push_group(group, kind, scale, interval_scale);
+        }
+        for group in &wave.guardians {
+            self.push_group(group, SpawnKind::Guardian(group.size), scale, interval_scale);
+        }
+        self.queue.sort_by(|a, b| b.at.partial_cmp(&a.at).unwrap_or(Ordering::Equal));
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn push_group<S>(
+        &mut self, group: &SpawnGroup<S>, kind: SpawnKind, scale: f32, interval_scale: f32,
+    ) {
+        for i in 0..group.count {
+            self.queue.push(PendingSpawn {
+                at: (i as f32).mul_add(group.interval, group.delay) * interval_scale,
+                kind,
+                edge: group.edge,
+                speed: (group.speed.0 * scale, group.speed.1 * scale),
+                angvel: group.angvel,
+            });
+        }
+    }
+}
+
+//----------------------------------------------------------------
+
+pub struct WavePlugin;
+
+impl Plugin for WavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<WaveStartedEvent>()
+            .add_startup_system(load_waves)
+            .add_system_set(SystemSet::on_enter(AppState::Game).with_system(reset_waves))
+            .add_fixed_system_set(
+                SimulationStage::Gameplay,
+                SystemSet::new()
+                    .with_run_criteria(run_in_game)
+                    .with_system(wave_system.before(StepOrder::Asteroids)),
+            );
+    }
+}
+
+//----------------------------------------------------------------
+
+/// Load the wave file. Without it the arena falls back to its classic
+/// ever-accelerating spawn timers. Neither is used with a `Director`.
+fn load_waves(mut commands: Commands) {
+    let waves: Waves = match Waves::load(&asset_path(WAVES_PATH)) {
+        Ok(waves) => {
+            info!("Loaded {} waves from `{WAVES_PATH}`", waves.definitions.len());
+            waves
+        }
+        Err(err) => {
+            error!("Wave file not loaded, using timed spawns: {err}");
+            Waves::default()
+        }
+    };
+    commands.insert_resource(WaveState::new(&waves));
+    commands.insert_resource(waves);
+}
+
+fn reset_waves(mut commands: Commands, waves: Res<Waves>) {
+    commands.insert_resource(WaveState::new(&waves));
+}
+
+/// Play the waves, paced by the `Difficulty`. Members which would exceed the
+/// `max_asteroid_count` or `max_guardian_count` are skipped, as timed spawns
+/// are.
+#[allow(clippy::too_many_arguments)]
+fn wave_system(
+    gamestate: Res<State<AppGameState>>, config: Res<GameConfig>, difficulty: Res<Difficulty>,
+    waves: Res<Waves>, director: Option<Res<Director>>, mut wave: ResMut<WaveState>,
+    mut game_rng: ResMut<GameRng>, asteroids: Query<(), With<Asteroid>>,
+    guardians: Query<(), With<Guardian>>, warnings: Query<&SpawnWarning>,
+    mut wave_started_events: EventWriter<WaveStartedEvent>,
+    mut spawn_orders: EventWriter<SpawnOrder>,
+) {
+    if gamestate.current() != &AppGameState::Game || waves.is_empty() || director.is_some() {
+        return; // early exit.
+    }
+
+    // The wave is cleared once everything spawned and no asteroid is left.
+    if wave.queue.is_empty() && warnings.is_empty() && asteroids.is_empty() {
+        wave.rest_timer.tick(fixed_delta());
+        if !wave.rest_timer.finished() {
+            return;
+        }
+        wave.start_next(&waves, difficulty.preset().spawn_interval);
+        wave_started_events.send(WaveStartedEvent { wave: wave.counter });
+    }
+
+    wave.elapsed += TIME_STEP;
+    let (pending_asteroids, pending_guardians): (usize, usize) = pending_spawns(&warnings);
+    let mut n_asteroid: usize = asteroids.iter().count() + pending_asteroids;
+    let mut n_guardian: usize = guardians.iter().count() + pending_guardians;
+    let rng = &mut game_rng.spawning;
+    while wave.queue.last().is_some_and(|spawn| spawn.at <= wave.elapsed) {
+        let Some(spawn) = wave.queue.pop() else { break };
+
+        let (count, max_count): (&mut usize, usize) = match spawn.kind {
+            SpawnKind::Asteroid(..) => (&mut n_asteroid, config.arena.max_asteroid_count),
+            SpawnKind::Guardian(_) => (&mut n_guardian, config.arena.max_guardian_count),
+        };
+        if *count >= max_count {
+            continue;
+        }
+        *count += 1usize;
+
+        let speed_scale: f32 = match spawn.kind {
+            SpawnKind::Asteroid(..) => config.arena.asteroid_speed_scale,
+            SpawnKind::Guardian(_) => 1f32,
+        };
+        let speed: f32 = rng.gen_range(spawn.speed.0..=spawn.speed.1) * speed_scale;
+        let angvel: f32 = if spawn.angvel.0 < spawn.angvel.1 {
+            rng.gen_range(spawn.angvel.0..spawn.angvel.1)
+        } else {
+            spawn.angvel.0
+        };
+        spawn_orders.send(SpawnOrder { kind: spawn.kind, edge: spawn.edge, speed, angvel });
+    }
+}
+
+//----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_utils::*;
+
+    #[test]
+    fn test_bundled_waves_parse() {
+        let waves = Waves::parse(include_str!("../assets/waves.ron")).unwrap();
+        assert!(!waves.is_empty());
+    }
+
+    #[test]
+    fn test_wave_queue_order() {
+        let waves = Waves::parse(include_str!("../assets/waves.ron")).unwrap();
+        let mut state = WaveState::new(&waves);
+        state.start_next(&waves, 1f32);
+        assert_eq!(state.counter, 1u32);
+        assert!(state.queue.windows(2).all(|pair| pair[0].at >= pair[1].at));
+    }
+
+    #[test]
+    fn test_invalid_speed_range() {
+        let source = "(waves: [(asteroids: [(size: Big, count: 1, speed: (50.0, 10.0))])])";
+        assert!(Waves::parse(source).is_err());
+
+        let group = "(size: Big, count: 1, speed: (10.0, 50.0)";
+        for source in [
+            format!("(waves: [(asteroids: [{group})])], warmup: -1.0)"),
+            format!("(waves: [(asteroids: [{group})])], warmup: NaN)"),
+            format!("(waves: [(asteroids: [{group})])], loop_speed_scale: -1.0)"),
+            format!("(waves: [(asteroids: [{group}, delay: -1.0)])])"),
+            format!("(waves: [(asteroids: [{group}, delay: NaN)])])"),
+            format!("(waves: [(asteroids: [{group}, interval: -0.5)])])"),
+            format!("(waves: [(asteroids: [{group}, interval: NaN)])])"),
+            "(waves: [(asteroids: [(size: Big, count: 1, speed: (10.0, inf))])])".to_owned(),
+            "(waves: [(asteroids: [(size: Big, count: 1, speed: (NaN, 50.0))])])".to_owned(),
+            format!("(waves: [(asteroids: [{group}, angvel: (1.0, -1.0))])])"),
+            format!("(waves: [(asteroids: [{group}, angvel: (-inf, 1.0))])])"),
+            format!("(waves: [(asteroids: [{group}, angvel: (0.0, NaN))])])"),
+        ] {
+            assert!(Waves::parse(&source).is_err(), "{source}");
+        }
+        assert!(Waves::parse(&format!("(waves: [(asteroids: [{group})])])")).is_ok());
+        assert!(Waves::parse(&format!("(waves: [(asteroids: [{group}, angvel: (-1.0, 1.0))])])"))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_difficulty_paces_waves() {
+        let remaining = |difficulty: Difficulty| {
+            let mut app = GameAppBuilder::new()
+                .headless(true)
+                .start_in_game(true)
+                .difficulty(difficulty)
+                .seed(Some(15u64))
+                .build();
+            run_ticks(&mut app, 300u64);
+            let wave = app.world.resource::<WaveState>();
+            assert_eq!(wave.counter, 1u32);
+            wave.queue.len()
+        };
+        // Three seconds into the first wave, whose last members spawn 4.5 and
+        // 6 seconds in on easy, but 1.5 and 2 seconds in on insane.
+        assert_eq!(remaining(Difficulty::Easy), 2usize);
+        assert_eq!(remaining(Difficulty::Insane), 0usize);
+    }
+}
@@ -0,0 +1,177 @@
+use bevy::sprite::Mesh2dHandle;
+use bevy_rapier2d::rapier::math::Isometry;
+
+use crate::prelude::*;
+
+/// Body of a wraparound arena that is drawn and collides across the edges,
+/// through three `Ghost` copies offset by the size of the arena.
+#[derive(Debug, Default, Component)]
+pub struct Wrapping;
+
+/// Copy of a `Wrapping` body on the far side of the arena. A contact with a
+/// ghost is a contact with its `source`, see `ghost_source`.
+#[derive(Debug, Component)]
+pub struct Ghost {
+    pub source: Entity,
+    /// Axes the ghost is offset along, `1` or `0` each.
+    axes: Vec2,
+}
+
+impl Ghost {
+    /// Offset of the ghost of a body at `position`, towards the edges the
+    /// body is closest to. A ghost far from any edge is out of sight and
+    /// out of reach.
+    fn offset(&self, position: Vec2, arena_size: Vec2) -> Vec2 {
+        position.signum().neg() * arena_size * self.axes
+    }
+}
+
+/// Tag of the `Wrapping` bodies whose ghosts are spawned.
+#[derive(Component)]
+struct Ghosted;
+
+/// The body `entity` stands for in contacts: its source for a ghost, itself
+/// otherwise.
+pub fn ghost_source(ghosts: &Query<&Ghost>, entity: Entity) -> Entity {
+    ghosts.get(entity).map_or(entity, |ghost| ghost.source)
+}
+
+//----------------------------------------------------------------
+
+pub struct WrapPlugin;
+
+impl Plugin for WrapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_update(AppState::Game).with_system(ghost_sprites))
+            .add_fixed_system_set(
+                SimulationStage::PreStep,
+                SystemSet::new().with_run_criteria(run_in_game).with_system(spawn_ghosts),
+            )
+            .add_fixed_system_set(
+                SimulationStage::Spawn,
+                SystemSet::new()
+                    .with_run_criteria(run_in_game)
+                    .with_system(place_ghosts)
+                    .with_system(despawn_ghosts),
+            );
+    }
+}
+
+//----------------------------------------------------------------
+
+/// Copy the drawing of the sources, be it a sprite or a mesh.
+#[allow(clippy::type_complexity)]
+fn spawn_ghosts(
+    mut commands: Commands, bounds: Res<ArenaBounds>, mode: Res<ArenaMode>,
+    query: Query<
+        (
+            Entity,
+            &Transform,
+            &Collider,
+            Option<(&Sprite, &Handle<Image>)>,
+            Option<&Mesh2dHandle>,
+            Option<&Handle<ColorMaterial>>,
+        ),
+        (With<Wrapping>, Without<Ghosted>),
+    >,
+) {
+    if *mode != ArenaMode::Wraparound {
+        return; // early exit.
+    }
+    let arena_size: Vec2 = bounds.size;
+
+    for (entity, transform, collider, sprite, mesh, material) in &query {
+        for axes in [Vec2::X, Vec2::Y, Vec2::ONE] {
+            let ghost = Ghost { source: entity, axes };
+            let offset: Vec2 = ghost.offset(transform.translation.truncate(), arena_size);
+            let mut ghost_entity = commands.spawn((
+                SpatialBundle::from_transform(
+                    transform.with_translation(transform.translation + offset.extend(0f32)),
+                ),
+                ghost,
+                ForState { states: vec![AppState::Game] },
+                RigidBody::KinematicPositionBased,
+                collider.clone(),
+                ColliderScale::Relative(Vec2::ONE),
+            ));
+            if let Some((sprite, texture)) = sprite {
+                ghost_entity.insert((sprite.clone(), texture.clone()));
+            }
+            if let Some(mesh) = mesh {
+                ghost_entity.insert(mesh.clone());
+            }
+            // Shared, so the ghosts change color with their source.
+            if let Some(material) = material {
+                ghost_entity.insert(material.clone());
+            }
+        }
+        commands.entity(entity).insert(Ghosted);
+    }
+}
+
+/// Follow the sources, and their collider once built from their sprite.
+///
+/// A ghost jumping to the other side of the arena, as its source wraps
+/// around or crosses the middle, is teleported there. Moved like a kinematic
+/// body, it would kick whatever it touches, such as its source arriving
+/// where it was, at the speed of the jump.
+#[allow(clippy::type_complexity)]
+fn place_ghosts(
+    bounds: Res<ArenaBounds>, mut context: ResMut<RapierContext>,
+    sources: Query<
+        (&Transform, &Collider, Option<&ColliderScale>, ChangeTrackers<Collider>),
+        (With<Wrapping>, Without<Ghost>),
+    >,
+    mut ghosts: Query<
+        (&Ghost, &mut Transform, &mut Collider, &mut ColliderScale, Option<&RapierRigidBodyHandle>),
+        Without<Wrapping>,
+    >,
+) {
+    let arena_size: Vec2 = bounds.size;
+    let physics_scale: f32 = context.physics_scale();
+
+    for (ghost, mut transform, mut collider, mut scale, handle) in &mut ghosts {
+        let Ok((source_transform, source_collider, source_scale, tracker)) =
+            sources.get(ghost.source)
+        else {
+            continue;
+        };
+        let offset: Vec2 = ghost.offset(source_transform.translation.truncate(), arena_size);
+        let translation: Vec3 = source_transform.translation + offset.extend(0f32);
+        let jump: Vec2 = (translation - transform.translation).truncate();
+        *transform = source_transform.with_translation(translation);
+
+        let body = handle.and_then(|handle| context.bodies.get_mut(handle.0));
+        if let Some(body) = body.filter(|_| jump.abs().cmpge(arena_size / 2f32).any()) {
+            let position: Vec2 = translation.truncate() / physics_scale;
+            let angle: f32 = transform.rotation.to_scaled_axis().z;
+            body.set_position(Isometry::new(position.into(), angle), false);
+        }
+
+        if tracker.is_changed() {
+            *collider = source_collider.clone();
+            *scale = source_scale.copied().unwrap_or(ColliderScale::Relative(Vec2::ONE));
+        }
+    }
+}
+
+fn despawn_ghosts(
+    mut commands: Commands, ghosts: Query<(Entity, &Ghost)>, sources: Query<(), With<Wrapping>>,
+) {
+    for (entity, ghost) in &ghosts {
+        if sources.get(ghost.source).is_err() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Draw the ghosts like their source, e.g. blinking with the ship.
+fn ghost_sprites(
+    mut ghosts: Query<(&Ghost, &mut Sprite)>, sources: Query<&Sprite, Without<Ghost>>,
+) {
+    for (ghost, mut sprite) in &mut ghosts {
+        if let Ok(source_sprite) = sources.get(ghost.source) {
+            sprite.color = source_sprite.color;
+        }
+    }
+}